@@ -3,6 +3,19 @@ import * as anchor from "@coral-xyz/anchor";
 import { Connection, PublicKey } from "@solana/web3.js";
 import { BtcLightClient } from "../../../target/types/btc_light_client";
 import { loadWalletFromEnv, getRpcUrl } from "../utils/wallet";
+import { JsonRpcClient } from "../utils/json-rpc";
+
+const DIFFICULTY_ADJUSTMENT_INTERVAL = 2016;
+const NETWORKS = ["mainnet", "testnet3", "testnet4", "signet", "regtest"];
+
+// Network whose consensus rules the light client enforces, from BITCOIN_NETWORK
+function getNetwork(): any {
+    const network = process.env.BITCOIN_NETWORK || "testnet4";
+    if (!NETWORKS.includes(network)) {
+        throw new Error(`BITCOIN_NETWORK must be one of ${NETWORKS.join(", ")}`);
+    }
+    return { [network]: {} };
+}
 
 export async function initializeBtcLightClient() {
     const connection = new Connection(getRpcUrl(), "confirmed");
@@ -47,11 +60,31 @@ export async function initializeBtcLightClient() {
     //     ),
     // };
 
+    // chainwork and difficulty period data are read from the bitcoin node
+    const { env } = process;
+    const rpc = new JsonRpcClient({
+        url: env.BITCOIN_RPC_URL || "http://127.0.0.1:18443",
+        auth: env.BITCOIN_RPC_AUTH || "test:1234",
+    });
+    const genesisHeader = await getBtcBlockHeader(rpc, await getBtcBlockHash(rpc, genesisBlock.height));
+    const chainwork = Buffer.from(genesisHeader.chainwork, "hex");
+    const periodStartHeight = genesisBlock.height - (genesisBlock.height % DIFFICULTY_ADJUSTMENT_INTERVAL);
+    const periodStartHeader = await getBtcBlockHeader(rpc, await getBtcBlockHash(rpc, periodStartHeight));
+    const periodStartTime = periodStartHeader.time as number;
+    const prevBlockTimes: number[] = [];
+    for (let height = genesisBlock.height - 10; height < genesisBlock.height; height++) {
+        const header = await getBtcBlockHeader(rpc, await getBtcBlockHash(rpc, height));
+        prevBlockTimes.push(header.time as number);
+    }
+
     console.log("Initializing BTC Light Client with genesis block:", {
         height: genesisBlock.height,
         hash: genesisBlock.hash.toString('hex'),
         time: genesisBlock.time,
-        target: genesisBlock.target.toString('hex')
+        target: genesisBlock.target.toString('hex'),
+        chainwork: chainwork.toString('hex'),
+        periodStartTime,
+        prevBlockTimes,
     });
 
     try {
@@ -59,9 +92,14 @@ export async function initializeBtcLightClient() {
             .initialize(
                 new anchor.BN(genesisBlock.height),
                 Array.from(genesisBlock.hash),
-                genesisBlock.time,
                 Array.from(genesisBlock.target),
-                false,  // isTestnet
+                {
+                  blockTime: genesisBlock.time,
+                  prevBlockTimes: prevBlockTimes,
+                  chainwork: Array.from(chainwork),
+                  periodStartTime: periodStartTime,
+                },
+                getNetwork(),
                 new anchor.BN(3)  // minConfirmations
             )
             .accounts({})
@@ -72,4 +110,16 @@ export async function initializeBtcLightClient() {
         console.error("Failed to initialize BTC Light Client:", error);
         throw error;
     }
-} 
\ No newline at end of file
+}
+
+async function getBtcBlockHash(rpc: JsonRpcClient, height: number): Promise<string> {
+    const res = await rpc.req("getblockhash", [height]);
+    if (res.error) throw new Error("Failed to get block hash: " + JSON.stringify(res));
+    return res.result as string;
+}
+
+async function getBtcBlockHeader(rpc: JsonRpcClient, hash: string): Promise<any> {
+    const res = await rpc.req("getblockheader", [hash, true]);
+    if (res.error) throw new Error("Failed to get block header: " + JSON.stringify(res));
+    return res.result;
+}
@@ -0,0 +1,66 @@
+import { Program, Wallet } from "@coral-xyz/anchor";
+import { Connection, Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import * as anchor from "@coral-xyz/anchor";
+import { loadWalletFromEnv, getRpcUrl } from "../utils/wallet";
+import { BtcLightClient } from "../../../target/types/btc_light_client";
+
+// 8 discriminator + 8 base height + 1024 slots of 160 bytes
+const HEADER_STORE_SPACE = 8 + 8 + 1024 * 160;
+
+export async function initializeHeaderStore() {
+    const connection = new Connection(getRpcUrl(), "confirmed");
+    const wallet = new Wallet(loadWalletFromEnv());
+
+    console.log("current wallet", wallet.publicKey.toString());
+
+    // Create provider
+    const provider = new anchor.AnchorProvider(connection, wallet, {});
+    anchor.setProvider(provider);
+
+    const program = anchor.workspace.BtcLightClient as Program<BtcLightClient>;
+
+    const [statePda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("btc_light_client")],
+        program.programId
+    );
+    const state = await program.account.btcLightClientState.fetch(statePda);
+
+    if (!state.headerStore.equals(PublicKey.default)) {
+        console.log("Header store already initialized:", state.headerStore.toString());
+        return;
+    }
+
+    const [tipBlockHashEntry] = PublicKey.findProgramAddressSync(
+        [
+            Buffer.from("block_hash_entry"),
+            state.latestBlockHeight.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+    );
+
+    // The store is too large to be created by the program, so allocate it in the same transaction
+    const headerStore = Keypair.generate();
+    const lamports = await connection.getMinimumBalanceForRentExemption(HEADER_STORE_SPACE);
+
+    const tx = await program.methods
+        .initializeHeaderStore()
+        .accountsPartial({
+            headerStore: headerStore.publicKey,
+            tipBlockHashEntry,
+            authority: wallet.publicKey,
+        })
+        .preInstructions([
+            SystemProgram.createAccount({
+                fromPubkey: wallet.publicKey,
+                newAccountPubkey: headerStore.publicKey,
+                space: HEADER_STORE_SPACE,
+                lamports,
+                programId: program.programId,
+            }),
+        ])
+        .signers([headerStore])
+        .rpc();
+
+    console.log("Header store initialized:", headerStore.publicKey.toString());
+    console.log(`Initialized with tx: ${tx}`);
+}
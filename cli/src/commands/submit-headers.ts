@@ -9,6 +9,7 @@ let isRunning = false;
 let intervalId: NodeJS.Timeout;
 
 const MAX_REORG = 50; // Maximum number of blocks to check for reorg
+const HEADERS_PER_TX = 10; // Larger batches go through the header buffer
 
 export async function submitHeaders(options: { daemon?: boolean } = {}) {
     if (isRunning) {
@@ -85,6 +86,12 @@ async function submitHeadersOnce() {
         return;
     }
 
+    // Headers live in the ring buffer once one is attached, otherwise in per-height accounts
+    const headerStore = state.headerStore.equals(PublicKey.default) ? null : state.headerStore;
+    const storedHashes = headerStore
+        ? await program.account.headerStore.fetch(headerStore)
+        : null;
+
     // Find common ancestor in case of reorg
     let commonHeight = mirrorLatestHeight;
 
@@ -94,26 +101,16 @@ async function submitHeadersOnce() {
         const btcHashLE = Buffer.from(btcHash, 'hex').reverse().toString('hex');
 
         // Get stored hash from light client
-        const [blockHashPda] = PublicKey.findProgramAddressSync(
-            [
-                Buffer.from("block_hash_entry"),
-                new anchor.BN(height).toArrayLike(Buffer, "le", 8),
-            ],
-            program.programId
-        );
+        const storedHash = await getStoredHash(program, storedHashes, height);
+        if (storedHash === null) {
+            // Block is not stored, which means we've found our starting point
+            console.log(`No stored block hash found at height ${height}, using as starting point`);
+            commonHeight = height;
+            break;
+        }
 
-        try {
-            const blockHashAccount = await program.account.blockHashEntry.fetch(blockHashPda);
-            const storedHash = Buffer.from(blockHashAccount.hash).toString('hex');
-
-            if (btcHashLE === storedHash) {
-                console.log(`Found common hash at height ${height}: ${btcHash} (LE: ${btcHashLE})`);
-                commonHeight = height;
-                break;
-            }
-        } catch (error) {
-            // Account does not exist, which means we've found our starting point
-            console.log(`No block hash account found at height ${height}, using as starting point`);
+        if (btcHashLE === storedHash) {
+            console.log(`Found common hash at height ${height}: ${btcHash} (LE: ${btcHashLE})`);
             commonHeight = height;
             break;
         }
@@ -130,7 +127,8 @@ async function submitHeadersOnce() {
         rpc,
         commonHeight,  // found common ancestor
         btcTipHeight,
-        maxBlocks
+        maxBlocks,
+        headerStore
     );
 }
 
@@ -146,7 +144,8 @@ async function submitBlockHeaders(
     rpc: JsonRpcClient,
     currentHeight: number,
     tipHeight: number,
-    maxBlocks: number
+    maxBlocks: number,
+    headerStore: PublicKey | null
 ) {
     const targetHeight = Math.min(tipHeight, currentHeight + maxBlocks);
     const headers: Buffer[] = [];
@@ -159,26 +158,62 @@ async function submitBlockHeaders(
         console.log(`Fetched header at height ${height}: ${hash}`);
     }
 
-    // create block hash accounts
-    const remainingAccounts = await Promise.all(
-        headers.map((_, i) =>
-            createBlockHashAccountIfNeeded(program, provider, currentHeight + i + 1)
-        )
-    );
+    let accounts;
+    let remainingAccounts: anchor.web3.AccountMeta[] = [];
+    if (headerStore) {
+        accounts = { parentBlockHashEntry: null, headerStore, submitterAllowlist: null };
+    } else {
+        // create block hash accounts
+        remainingAccounts = await Promise.all(
+            headers.map((_, i) =>
+                createBlockHashAccountIfNeeded(program, provider, currentHeight + i + 1)
+            )
+        );
+
+        // wait for create block hash accounts to be processed
+        console.log("Waiting for create block hash accounts to be processed...");
+        await new Promise(resolve => setTimeout(resolve, 5000));
 
-    // wait for create block hash accounts to be processed
-    console.log("Waiting for create block hash accounts to be processed...");
-    await new Promise(resolve => setTimeout(resolve, 5000));
+        // the headers build on the common ancestor, which may be below the current tip
+        accounts = {
+            parentBlockHashEntry: getBlockHashPda(program, currentHeight),
+            headerStore: null,
+            submitterAllowlist: null,
+        };
+    }
 
     console.log(`Submitting ${headers.length} headers from height ${currentHeight + 1}`);
 
-    await program.methods
-        .submitBlockHeaders(new anchor.BN(currentHeight + 1), Buffer.concat(headers))
-        .accounts({})
+    let submit;
+    if (headers.length <= HEADERS_PER_TX) {
+        submit = program.methods
+            .submitBlockHeaders(new anchor.BN(currentHeight + 1), Buffer.concat(headers));
+    } else {
+        // Too many headers for one transaction: upload them into the header buffer first,
+        // which validates every chunk against the parent block. Without a header store the
+        // finalize transaction still needs one entry account per header.
+        await program.methods
+            .initHeaderBuffer(new anchor.BN(currentHeight + 1))
+            .accountsPartial({
+                parentBlockHashEntry: accounts.parentBlockHashEntry,
+                headerStore: accounts.headerStore,
+            })
+            .rpc();
+        for (let i = 0; i < headers.length; i += HEADERS_PER_TX) {
+            await program.methods
+                .appendHeaderBuffer(Buffer.concat(headers.slice(i, i + HEADERS_PER_TX)))
+                .accounts({})
+                .rpc();
+        }
+        submit = program.methods.finalizeHeaderBuffer();
+    }
+
+    await submit
+        .accountsPartial(accounts)
         .remainingAccounts(remainingAccounts)
         .preInstructions([
             anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({
-                units: 500_000,
+                units: 1_400_000,
             }),
         ])
         .rpc();
@@ -198,11 +233,32 @@ async function getBtcBlockHeader(rpc: JsonRpcClient, hash: string): Promise<stri
     return res.result as string;
 }
 
-async function createBlockHashAccountIfNeeded(
+const HEADER_STORE_CAPACITY = 1024;
+
+// Returns the little-endian hash stored for `height`, or null if the light client does not keep it
+async function getStoredHash(
     program: Program<BtcLightClient>,
-    provider: AnchorProvider,
+    headerStore: { headers: { height: anchor.BN; hash: number[] }[] } | null,
     height: number
-): Promise<anchor.web3.AccountMeta> {
+): Promise<string | null> {
+    if (headerStore) {
+        const slot = headerStore.headers[height % HEADER_STORE_CAPACITY];
+        return slot.height.toNumber() === height
+            ? Buffer.from(slot.hash).toString('hex')
+            : null;
+    }
+
+    try {
+        const blockHashAccount = await program.account.blockHashEntry.fetch(
+            getBlockHashPda(program, height)
+        );
+        return Buffer.from(blockHashAccount.hash).toString('hex');
+    } catch (error) {
+        return null;
+    }
+}
+
+function getBlockHashPda(program: Program<BtcLightClient>, height: number): PublicKey {
     const [blockHashPda] = PublicKey.findProgramAddressSync(
         [
             Buffer.from("block_hash_entry"),
@@ -210,14 +266,20 @@ async function createBlockHashAccountIfNeeded(
         ],
         program.programId
     );
+    return blockHashPda;
+}
+
+async function createBlockHashAccountIfNeeded(
+    program: Program<BtcLightClient>,
+    provider: AnchorProvider,
+    height: number
+): Promise<anchor.web3.AccountMeta> {
+    const blockHashPda = getBlockHashPda(program, height);
 
     const accountInfo = await provider.connection.getAccountInfo(blockHashPda);
     if (!accountInfo) {
         await program.methods
-            .createBlockHashAccount(
-                new anchor.BN(height),
-                Array.from(new Uint8Array(32))
-            )
+            .createBlockHashAccount(new anchor.BN(height))
             .accounts({})
             .rpc();
     }
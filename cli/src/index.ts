@@ -1,6 +1,7 @@
 import { Command } from "commander";
 import { initializeBitvmBridge } from "./commands/initialize-bridge";
 import { initializeBtcLightClient } from "./commands/initialize-btc-light-client";
+import { initializeHeaderStore } from "./commands/initialize-header-store";
 import { submitHeaders } from "./commands/submit-headers";
 import { updateSkipTxVerification } from "./commands/update-skip-tx-verification";
 
@@ -30,6 +31,18 @@ program
     }
   });
 
+program
+  .command("initialize-header-store")
+  .description("Move BTC Light Client header storage into a ring buffer account")
+  .action(async () => {
+    try {
+      await initializeHeaderStore();
+    } catch (error) {
+      console.error("Error:", error);
+      process.exit(1);
+    }
+  });
+
 program
   .command("submit-headers")
   .description("Submit Bitcoin block headers")
@@ -67,4 +67,28 @@ pub enum BitvmBridgeError {
 
     #[msg("Invalid pda account")]
     InvalidPdaAccount,
+
+    #[msg("Missing rent payer")]
+    MissingRentPayer,
+
+    #[msg("Invalid deposit script")]
+    InvalidDepositScript,
+
+    #[msg("Recipient does not match the commitment")]
+    InvalidRecipient,
+
+    #[msg("Bitcoin payments need a full transaction proof")]
+    PartialTxProof,
+
+    #[msg("Verified block is not canonical with enough confirmations")]
+    TxNotCanonical,
+
+    #[msg("Verified transaction does not pay the withdraw receiver")]
+    LPWithdrawNotPaid,
+
+    #[msg("Bridge state already has the current layout")]
+    BridgeStateUpToDate,
+
+    #[msg("Tx already claimed")]
+    TxAlreadyClaimed,
 }
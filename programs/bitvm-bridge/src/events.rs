@@ -61,8 +61,25 @@ pub struct LPWithdrawTimeoutUpdated {
     pub new_timeout: u64,
 }
 
+#[event]
+pub struct DepositScriptHashUpdated {
+    pub new_deposit_script_hash: [u8; 32],
+}
+
+#[event]
+pub struct MintAuthorityHandedOver {
+    pub mint: Pubkey,
+    pub new_authority: Pubkey,
+}
+
 #[event]
 pub struct OwnershipTransferred {
     pub previous_owner: Pubkey,
     pub new_owner: Pubkey,
 }
+
+#[event]
+pub struct BridgeStateMigrated {
+    pub old_space: u64,
+    pub new_space: u64,
+}
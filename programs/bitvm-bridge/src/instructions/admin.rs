@@ -1,7 +1,13 @@
 use crate::errors::BitvmBridgeError;
-use crate::events::{LPWithdrawTimeoutUpdated, OwnershipTransferred};
+use crate::events::{
+    DepositScriptHashUpdated, LPWithdrawTimeoutUpdated, MintAuthorityHandedOver,
+    OwnershipTransferred,
+};
 use crate::state::BridgeState;
 use anchor_lang::prelude::*;
+use anchor_spl::token::{
+    set_authority, spl_token::instruction::AuthorityType, Mint, SetAuthority, Token,
+};
 
 #[derive(Accounts)]
 pub struct UpdateBridgeParams<'info> {
@@ -126,6 +132,74 @@ pub fn set_lp_withdraw_timeout(ctx: Context<SetLPWithdrawTimeout>, timeout: u64)
     Ok(())
 }
 
+#[derive(Accounts)]
+pub struct SetDepositScriptHash<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state"],
+        bump,
+        constraint = bridge_state.owner == owner.key() @ BitvmBridgeError::UnauthorizedOwner
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+
+    pub owner: Signer<'info>,
+}
+
+pub fn set_deposit_script_hash(
+    ctx: Context<SetDepositScriptHash>,
+    deposit_script_hash: [u8; 32],
+) -> Result<()> {
+    ctx.accounts.bridge_state.deposit_script_hash = deposit_script_hash;
+
+    emit!(DepositScriptHashUpdated {
+        new_deposit_script_hash: deposit_script_hash,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct HandOverMintAuthority<'info> {
+    #[account(
+        seeds = [b"bridge_state"],
+        bump,
+        constraint = bridge_state.owner == owner.key() @ BitvmBridgeError::UnauthorizedOwner
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+
+    #[account(mut, address = bridge_state.mint_account)]
+    pub mint_account: Account<'info, Mint>,
+
+    /// The owner, still the mint authority of a bridge deployed before the bridge state PDA
+    /// minted
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Makes the bridge state PDA the mint authority, which every mint now goes through. Only
+/// needed for mints created while the owner was their authority.
+pub fn hand_over_mint_authority(ctx: Context<HandOverMintAuthority>) -> Result<()> {
+    let new_authority = ctx.accounts.bridge_state.key();
+    set_authority(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            SetAuthority {
+                current_authority: ctx.accounts.owner.to_account_info(),
+                account_or_mint: ctx.accounts.mint_account.to_account_info(),
+            },
+        ),
+        AuthorityType::MintTokens,
+        Some(new_authority),
+    )?;
+
+    emit!(MintAuthorityHandedOver {
+        mint: ctx.accounts.mint_account.key(),
+        new_authority,
+    });
+
+    Ok(())
+}
+
 #[derive(Accounts)]
 pub struct TransferOwnership<'info> {
     #[account(
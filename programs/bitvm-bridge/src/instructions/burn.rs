@@ -60,10 +60,10 @@ pub fn burn_token(
 
     emit!(BurnEvent {
         from: ctx.accounts.authority.key(),
-        btc_addr: btc_addr,
+        btc_addr,
         fee_rate,
         value: amount,
-        operator_id: operator_id,
+        operator_id,
     });
 
     Ok(())
@@ -36,22 +36,24 @@ pub struct Initialize<'info> {
     #[account(
         init,
         payer = owner,
-        mint::decimals = 8,
-        mint::authority = owner.key(),
-        mint::freeze_authority = owner.key(),
-
+        seeds = [b"bridge_state"],
+        bump,
+        space = BridgeState::SPACE,
     )]
-    // token address
-    pub mint_account: Account<'info, Mint>,
+    pub bridge_state: Account<'info, BridgeState>,
 
+    // The bridge state PDA is the only mint authority: every mint goes through the bridge,
+    // whether the owner mints a deposit or anyone mints to a committed recipient
     #[account(
         init,
         payer = owner,
-        seeds = [b"bridge_state"],
-        bump,
-        space = 8 + std::mem::size_of::<BridgeState>(),
+        mint::decimals = 8,
+        mint::authority = bridge_state.key(),
+        mint::freeze_authority = owner.key(),
+
     )]
-    pub bridge_state: Account<'info, BridgeState>,
+    // token address
+    pub mint_account: Account<'info, Mint>,
     /// CHECK: Validate address by deriving pda
     #[account(
         mut,
@@ -73,19 +75,22 @@ pub fn initialize(
     bridge_params: BridgeParams,
 ) -> Result<()> {
     // Cross Program Invocation (CPI)
-    // Invoking the create_metadata_account_v3 instruction on the token metadata program
+    // Invoking the create_metadata_account_v3 instruction on the token metadata program, signed
+    // by the bridge state PDA as mint authority
+    let bridge_seeds = &[&b"bridge_state"[..], &[ctx.bumps.bridge_state]];
     create_metadata_accounts_v3(
-        CpiContext::new(
+        CpiContext::new_with_signer(
             ctx.accounts.token_metadata_program.to_account_info(),
             CreateMetadataAccountsV3 {
                 metadata: ctx.accounts.metadata_account.to_account_info(),
                 mint: ctx.accounts.mint_account.to_account_info(),
-                mint_authority: ctx.accounts.owner.to_account_info(),
+                mint_authority: ctx.accounts.bridge_state.to_account_info(),
                 update_authority: ctx.accounts.owner.to_account_info(),
                 payer: ctx.accounts.owner.to_account_info(),
                 system_program: ctx.accounts.system_program.to_account_info(),
                 rent: ctx.accounts.rent.to_account_info(),
             },
+            &[&bridge_seeds[..]],
         ),
         DataV2 {
             name: token_metadata.name,
@@ -111,6 +116,7 @@ pub fn initialize(
     ctx.accounts.bridge_state.burn_paused = false;
     ctx.accounts.bridge_state.max_fee_rate = bridge_params.max_fee_rate;
     ctx.accounts.bridge_state.lp_withdraw_timeout = bridge_params.lp_withdraw_timeout;
+    ctx.accounts.bridge_state.deposit_script_hash = [0; 32];
 
     Ok(())
 }
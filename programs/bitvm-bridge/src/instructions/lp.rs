@@ -6,7 +6,10 @@ use crate::events::{
     ClaimLPWithdraw as ClaimLPWithdrawEvent, LPRegistered, LPStatusUpdated,
     RefundLPWithdraw as RefundLPWithdrawEvent, WithdrawByLP as WithdrawByLPEvent,
 };
-use crate::state::{BridgeState, LPRegister, LPState, LPStatus, LPWithdrawState};
+use crate::instructions::mint::{consume_tx_verified_state, paid_amount, require_canonical};
+use crate::state::{BridgeState, LPRegister, LPState, LPStatus, LPWithdrawState, TxClaimedState};
+use btc_light_client::program::BtcLightClient;
+use btc_light_client::state::BtcLightClientState;
 
 // Register LP instruction
 #[derive(Accounts)]
@@ -150,6 +153,7 @@ pub struct WithdrawByLP<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn withdraw_by_lp(
     ctx: Context<WithdrawByLP>,
     withdraw_id: u64,
@@ -249,6 +253,17 @@ pub struct ClaimLPWithdraw<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
     pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    // One Bitcoin payment backs at most one claim
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = TxClaimedState::SPACE,
+        seeds = [b"tx_claimed_state".as_ref(), btc_tx_id.as_ref()],
+        bump,
+    )]
+    pub tx_claimed_state: Account<'info, TxClaimedState>,
 
     // Bitcoin transaction verification accounts
     #[account(
@@ -258,18 +273,41 @@ pub struct ClaimLPWithdraw<'info> {
         bump,
     )]
     pub tx_verified_state: Option<Account<'info, btc_light_client::state::TxVerifiedState>>,
+
+    /// CHECK: receives the rent of the consumed tx_verified_state, checked by the light client
+    #[account(mut)]
+    pub tx_verified_state_payer: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        seeds = [b"btc_light_client"],
+        seeds::program = btc_light_client::ID,
+        bump,
+    )]
+    pub btc_light_client_state: Account<'info, BtcLightClientState>,
+    pub btc_light_client_program: Program<'info, BtcLightClient>,
+
+    /// CHECK: entry of the block the transaction was verified in, checked by the light client
+    pub block_hash_entry: Option<UncheckedAccount<'info>>,
+    /// CHECK: header store of the light client, checked by the light client
+    pub header_store: Option<UncheckedAccount<'info>>,
 }
 
 pub fn claim_lp_withdraw(
     ctx: Context<ClaimLPWithdraw>,
     _withdraw_id: u64,
-    _btc_tx_id: [u8; 32],
+    btc_tx_id: [u8; 32],
     amount_sats: u64,
 ) -> Result<()> {
     let bridge_state = &ctx.accounts.bridge_state;
     let lp_withdraw_state = &ctx.accounts.lp_withdraw_state;
     let lp_state = &ctx.accounts.lp_state;
     let tx_verified_state = &ctx.accounts.tx_verified_state;
+    let tx_claimed_state = &mut ctx.accounts.tx_claimed_state;
+
+    require!(
+        !tx_claimed_state.is_claimed,
+        BitvmBridgeError::TxAlreadyClaimed
+    );
 
     // Validate claim info
     require!(
@@ -288,6 +326,24 @@ pub fn claim_lp_withdraw(
             || (tx_verified_state.is_some() && tx_verified_state.as_ref().unwrap().is_verified),
         BitvmBridgeError::TxNotVerified
     );
+    if !bridge_state.skip_tx_verification {
+        let verified = tx_verified_state.as_ref().unwrap();
+        require_canonical(
+            &ctx.accounts.btc_light_client_program,
+            &ctx.accounts.btc_light_client_state,
+            verified,
+            ctx.accounts.block_hash_entry.as_ref(),
+            ctx.accounts.header_store.as_ref(),
+            btc_tx_id,
+        )?;
+        // The LP must have paid the receiver at least the claimed amount
+        require!(
+            paid_amount(verified, &lp_withdraw_state.receiver_script_hash) >= amount_sats,
+            BitvmBridgeError::LPWithdrawNotPaid
+        );
+    }
+
+    tx_claimed_state.is_claimed = true;
 
     let bridge_seeds = &[&b"bridge_state"[..], &[ctx.bumps.bridge_state]];
     let bridge_signer = &[&bridge_seeds[..]];
@@ -305,6 +361,19 @@ pub fn claim_lp_withdraw(
     );
     transfer(cpi_ctx, lp_withdraw_state.withdraw_amount)?;
 
+    // The withdraw state is closed by this claim, so the verification is no longer needed
+    if let Some(tx_verified_state) = tx_verified_state {
+        consume_tx_verified_state(
+            &ctx.accounts.btc_light_client_program,
+            &ctx.accounts.btc_light_client_state,
+            tx_verified_state,
+            ctx.accounts.tx_verified_state_payer.as_ref(),
+            bridge_state,
+            ctx.bumps.bridge_state,
+            btc_tx_id,
+        )?;
+    }
+
     emit!(ClaimLPWithdrawEvent {
         withdraw_id: lp_withdraw_state.id,
         lp_id: lp_state.id,
@@ -0,0 +1,75 @@
+use crate::errors::BitvmBridgeError;
+use crate::events::BridgeStateMigrated;
+use crate::state::{BridgeState, LegacyBridgeState};
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+
+#[derive(Accounts)]
+pub struct MigrateBridgeState<'info> {
+    /// CHECK: The bridge state in the legacy layout, which `Account` cannot deserialize yet;
+    /// the owner is checked against the legacy data
+    #[account(mut, seeds = [b"bridge_state"], bump, owner = crate::ID)]
+    pub bridge_state: UncheckedAccount<'info>,
+
+    /// Pays the rent of the added bytes (must be owner)
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Grows a bridge state written before `deposit_script_hash` existed to `BridgeState::SPACE`.
+/// The deposit script hash starts unset, so verified deposits are refused until the owner sets
+/// it with `set_deposit_script_hash`.
+pub fn migrate_bridge_state(ctx: Context<MigrateBridgeState>) -> Result<()> {
+    let state_info = ctx.accounts.bridge_state.to_account_info();
+    let old_space = state_info.data_len();
+    require!(
+        old_space != BridgeState::SPACE,
+        BitvmBridgeError::BridgeStateUpToDate
+    );
+    let legacy = LegacyBridgeState::try_from_account_data(&state_info.data.borrow())?;
+    require!(
+        legacy.owner == ctx.accounts.owner.key(),
+        BitvmBridgeError::UnauthorizedOwner
+    );
+
+    // Top the rent up for the new size; the added bytes start zeroed
+    let shortfall = Rent::get()?
+        .minimum_balance(BridgeState::SPACE)
+        .saturating_sub(state_info.lamports());
+    if shortfall > 0 {
+        transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.owner.to_account_info(),
+                    to: state_info.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    state_info.realloc(BridgeState::SPACE, true)?;
+
+    let state = BridgeState {
+        owner: legacy.owner,
+        mint_account: legacy.mint_account,
+        max_btc_per_mint: legacy.max_btc_per_mint,
+        min_btc_per_mint: legacy.min_btc_per_mint,
+        max_btc_per_burn: legacy.max_btc_per_burn,
+        min_btc_per_burn: legacy.min_btc_per_burn,
+        skip_tx_verification: legacy.skip_tx_verification,
+        burn_paused: legacy.burn_paused,
+        max_fee_rate: legacy.max_fee_rate,
+        lp_withdraw_timeout: legacy.lp_withdraw_timeout,
+        deposit_script_hash: [0; 32],
+    };
+    state.try_serialize(&mut &mut state_info.data.borrow_mut()[..])?;
+
+    emit!(BridgeStateMigrated {
+        old_space: old_space as u64,
+        new_space: BridgeState::SPACE as u64,
+    });
+
+    Ok(())
+}
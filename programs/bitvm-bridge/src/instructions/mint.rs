@@ -3,22 +3,33 @@ use crate::{
     events::MintEvent,
     state::{BridgeState, TxMintedState},
 };
-use anchor_lang::{prelude::*, solana_program::program_option::COption};
+use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
     token::{mint_to, Mint, MintTo, Token, TokenAccount},
 };
-use btc_light_client::state::TxVerifiedState;
+use btc_light_client::cpi::{accounts::CloseTxVerifiedState, close_tx_verified_state};
+use btc_light_client::program::BtcLightClient;
+use btc_light_client::query::get_tx_confirmations;
+use btc_light_client::state::{BtcLightClientState, TxVerifiedState};
 use btc_light_client::ID as BTC_LIGHT_CLIENT_PROGRAM_ID;
 
 #[derive(Accounts)]
 #[instruction(tx_id: [u8; 32])]
 pub struct MintToken<'info> {
+    /// The bridge owner, who picks the recipient; the tokens are minted by the bridge state PDA
     #[account(mut)]
     pub mint_authority: Signer<'info>,
 
+    /// Must be the recipient the deposit committed to, if it committed to one
+    #[account(
+        constraint = tx_verified_state
+            .as_ref()
+            .and_then(|state| state.recipient)
+            .unwrap_or(recipient.key()) == recipient.key() @ BitvmBridgeError::InvalidRecipient
+    )]
     pub recipient: SystemAccount<'info>,
-    #[account(mut)]
+    #[account(mut, address = bridge_state.mint_account)]
     pub mint_account: Account<'info, Mint>,
     #[account(
         init_if_needed,
@@ -55,18 +66,113 @@ pub struct MintToken<'info> {
         bump,
     )]
     pub tx_verified_state: Option<Account<'info, TxVerifiedState>>,
+
+    /// CHECK: receives the rent of the consumed tx_verified_state, checked by the light client
+    #[account(mut)]
+    pub tx_verified_state_payer: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        seeds = [b"btc_light_client"],
+        seeds::program = BTC_LIGHT_CLIENT_PROGRAM_ID,
+        bump,
+    )]
+    pub btc_light_client_state: Account<'info, BtcLightClientState>,
+    pub btc_light_client_program: Program<'info, BtcLightClient>,
+
+    /// CHECK: entry of the block the transaction was verified in, checked by the light client
+    pub block_hash_entry: Option<UncheckedAccount<'info>>,
+    /// CHECK: header store of the light client, checked by the light client
+    pub header_store: Option<UncheckedAccount<'info>>,
+}
+
+/// Hands a consumed tx_verified_state back to the light client, which closes it and refunds
+/// whoever paid for the verification. The bridge state PDA signs as the registered consumer.
+/// While the light client has no consumer registered the state is left open instead, which is
+/// safe because `tx_minted_state` already keeps a deposit from being minted twice and
+/// `tx_claimed_state` a payment from backing two LP withdraw claims.
+pub(crate) fn consume_tx_verified_state<'info>(
+    btc_light_client_program: &Program<'info, BtcLightClient>,
+    btc_light_client_state: &Account<'info, BtcLightClientState>,
+    tx_verified_state: &Account<'info, TxVerifiedState>,
+    tx_verified_state_payer: Option<&UncheckedAccount<'info>>,
+    bridge_state: &Account<'info, BridgeState>,
+    bridge_state_bump: u8,
+    tx_id: [u8; 32],
+) -> Result<()> {
+    if btc_light_client_state.tx_consumer == Pubkey::default() {
+        return Ok(());
+    }
+    let payer = tx_verified_state_payer.ok_or(BitvmBridgeError::MissingRentPayer)?;
+    let bridge_seeds = &[&b"bridge_state"[..], &[bridge_state_bump]];
+
+    close_tx_verified_state(
+        CpiContext::new_with_signer(
+            btc_light_client_program.to_account_info(),
+            CloseTxVerifiedState {
+                state: btc_light_client_state.to_account_info(),
+                tx_verified_state: tx_verified_state.to_account_info(),
+                payer: payer.to_account_info(),
+                consumer: bridge_state.to_account_info(),
+            },
+            &[&bridge_seeds[..]],
+        ),
+        tx_id,
+    )
+}
+
+/// Checks through the light client that the block the transaction was verified in is still
+/// on the canonical chain, with as many confirmations as verification requires
+pub(crate) fn require_canonical<'info>(
+    btc_light_client_program: &Program<'info, BtcLightClient>,
+    btc_light_client_state: &Account<'info, BtcLightClientState>,
+    tx_verified_state: &Account<'info, TxVerifiedState>,
+    block_hash_entry: Option<&UncheckedAccount<'info>>,
+    header_store: Option<&UncheckedAccount<'info>>,
+    tx_id: [u8; 32],
+) -> Result<()> {
+    let confirmations = get_tx_confirmations(
+        btc_light_client_program.to_account_info(),
+        btc_light_client_state.to_account_info(),
+        tx_verified_state.to_account_info(),
+        block_hash_entry.map(|account| account.to_account_info()),
+        header_store.map(|account| account.to_account_info()),
+        tx_id,
+    )?;
+    // Confirmations count the block itself, `min_confirmations` only the blocks on top of it
+    require!(
+        confirmations > btc_light_client_state.min_confirmations,
+        BitvmBridgeError::TxNotCanonical
+    );
+    Ok(())
 }
 
-pub fn mint_token(ctx: Context<MintToken>, _tx_id: [u8; 32], amount: u64) -> Result<()> {
+/// Total value of the verified outputs paying to `script_hash`. The light client only records
+/// an output together with every other output paying the same script, so this is all the
+/// transaction paid to it.
+pub(crate) fn paid_amount(verified: &TxVerifiedState, script_hash: &[u8; 32]) -> u64 {
+    verified
+        .outputs
+        .iter()
+        .filter(|output| &output.script_hash == script_hash)
+        .map(|output| output.value)
+        .sum()
+}
+
+/// Total value the transaction deposited to the bridge deposit script
+fn deposited_amount(verified: &TxVerifiedState, bridge_state: &BridgeState) -> Result<u64> {
+    let deposited = paid_amount(verified, &bridge_state.deposit_script_hash);
+    require!(deposited > 0, BitvmBridgeError::InvalidDepositScript);
+    Ok(deposited)
+}
+
+pub fn mint_token(ctx: Context<MintToken>, tx_id: [u8; 32], amount: u64) -> Result<()> {
     let bridge_state = &ctx.accounts.bridge_state;
     let tx_verified_state = &ctx.accounts.tx_verified_state;
     let tx_minted_state = &mut ctx.accounts.tx_minted_state;
 
-    // Check if the mint authority is the bridge owner or the mint authority is set
-    let mint_info = &ctx.accounts.mint_account;
+    // Only the bridge owner may mint to a recipient of its choice
     require!(
-        bridge_state.owner == ctx.accounts.mint_authority.key()
-            || mint_info.mint_authority == COption::Some(ctx.accounts.mint_authority.key()),
+        bridge_state.owner == ctx.accounts.mint_authority.key(),
         BitvmBridgeError::UnauthorizedMinter
     );
 
@@ -77,31 +183,193 @@ pub fn mint_token(ctx: Context<MintToken>, _tx_id: [u8; 32], amount: u64) -> Res
     );
 
     require!(
-        bridge_state.skip_tx_verification
-            || (tx_verified_state.is_some() && tx_verified_state.as_ref().unwrap().is_verified),
-        BitvmBridgeError::TxNotVerified
+        !tx_minted_state.is_minted,
+        BitvmBridgeError::TxAlreadyMinted
     );
 
+    if !bridge_state.skip_tx_verification {
+        let verified = tx_verified_state
+            .as_ref()
+            .filter(|state| state.is_verified)
+            .ok_or(BitvmBridgeError::TxNotVerified)?;
+        require_canonical(
+            &ctx.accounts.btc_light_client_program,
+            &ctx.accounts.btc_light_client_state,
+            verified,
+            ctx.accounts.block_hash_entry.as_ref(),
+            ctx.accounts.header_store.as_ref(),
+            tx_id,
+        )?;
+
+        // The minted amount is exactly what the proven deposit outputs carry
+        require!(
+            deposited_amount(verified, bridge_state)? == amount,
+            BitvmBridgeError::MismatchBtcAmount
+        );
+    }
+
+    tx_minted_state.is_minted = true;
+
+    // Mint tokens
+    let bridge_seeds = &[&b"bridge_state"[..], &[ctx.bumps.bridge_state]];
+    mint_to(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.mint_account.to_account_info(),
+                to: ctx.accounts.associated_token_account.to_account_info(),
+                authority: bridge_state.to_account_info(),
+            },
+            &[&bridge_seeds[..]],
+        ),
+        amount,
+    )?;
+
+    // The mint is recorded in tx_minted_state, so the verification is no longer needed
+    if let Some(tx_verified_state) = tx_verified_state {
+        consume_tx_verified_state(
+            &ctx.accounts.btc_light_client_program,
+            &ctx.accounts.btc_light_client_state,
+            tx_verified_state,
+            ctx.accounts.tx_verified_state_payer.as_ref(),
+            bridge_state,
+            ctx.bumps.bridge_state,
+            tx_id,
+        )?;
+    }
+
+    emit!(MintEvent {
+        to: ctx.accounts.recipient.key(),
+        value: amount,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(tx_id: [u8; 32])]
+pub struct MintToCommittedRecipient<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The recipient the pegin transaction committed to
+    #[account(
+        constraint = tx_verified_state.recipient == Some(recipient.key()) @ BitvmBridgeError::InvalidRecipient
+    )]
+    pub recipient: SystemAccount<'info>,
+    #[account(mut, address = bridge_state.mint_account)]
+    pub mint_account: Account<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_account,
+        associated_token::authority = recipient,
+    )]
+    pub associated_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"bridge_state"],
+        bump,
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = TxMintedState::SPACE,
+        seeds = [b"tx_minted_state".as_ref(), tx_id.as_ref()],
+        bump,
+    )]
+    pub tx_minted_state: Account<'info, TxMintedState>,
+
+    #[account(
+        mut,
+        seeds = [b"tx_verified_state", tx_id.as_ref()],
+        seeds::program = BTC_LIGHT_CLIENT_PROGRAM_ID,
+        bump,
+    )]
+    pub tx_verified_state: Account<'info, TxVerifiedState>,
+
+    /// CHECK: receives the rent of the consumed tx_verified_state, checked by the light client
+    #[account(mut)]
+    pub tx_verified_state_payer: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"btc_light_client"],
+        seeds::program = BTC_LIGHT_CLIENT_PROGRAM_ID,
+        bump,
+    )]
+    pub btc_light_client_state: Account<'info, BtcLightClientState>,
+    pub btc_light_client_program: Program<'info, BtcLightClient>,
+
+    /// CHECK: entry of the block the transaction was verified in, checked by the light client
+    pub block_hash_entry: Option<UncheckedAccount<'info>>,
+    /// CHECK: header store of the light client, checked by the light client
+    pub header_store: Option<UncheckedAccount<'info>>,
+}
+
+/// Mints a verified deposit to the recipient committed in the pegin transaction. Anyone may
+/// call it, and the proof is always required.
+pub fn mint_to_committed_recipient(
+    ctx: Context<MintToCommittedRecipient>,
+    tx_id: [u8; 32],
+) -> Result<()> {
+    let bridge_state = &ctx.accounts.bridge_state;
+    let tx_verified_state = &ctx.accounts.tx_verified_state;
+    let tx_minted_state = &mut ctx.accounts.tx_minted_state;
+
     require!(
         !tx_minted_state.is_minted,
         BitvmBridgeError::TxAlreadyMinted
     );
+    require!(
+        tx_verified_state.is_verified,
+        BitvmBridgeError::TxNotVerified
+    );
+    require_canonical(
+        &ctx.accounts.btc_light_client_program,
+        &ctx.accounts.btc_light_client_state,
+        tx_verified_state,
+        ctx.accounts.block_hash_entry.as_ref(),
+        ctx.accounts.header_store.as_ref(),
+        tx_id,
+    )?;
+
+    let amount = deposited_amount(tx_verified_state, bridge_state)?;
+    require!(
+        amount >= bridge_state.min_btc_per_mint && amount <= bridge_state.max_btc_per_mint,
+        BitvmBridgeError::InvalidPeginAmount
+    );
 
     tx_minted_state.is_minted = true;
 
-    // Mint tokens
+    let bridge_seeds = &[&b"bridge_state"[..], &[ctx.bumps.bridge_state]];
     mint_to(
-        CpiContext::new(
+        CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
             MintTo {
                 mint: ctx.accounts.mint_account.to_account_info(),
                 to: ctx.accounts.associated_token_account.to_account_info(),
-                authority: ctx.accounts.mint_authority.to_account_info(),
+                authority: bridge_state.to_account_info(),
             },
+            &[&bridge_seeds[..]],
         ),
         amount,
     )?;
 
+    consume_tx_verified_state(
+        &ctx.accounts.btc_light_client_program,
+        &ctx.accounts.btc_light_client_state,
+        tx_verified_state,
+        Some(&ctx.accounts.tx_verified_state_payer),
+        bridge_state,
+        ctx.bumps.bridge_state,
+        tx_id,
+    )?;
+
     emit!(MintEvent {
         to: ctx.accounts.recipient.key(),
         value: amount,
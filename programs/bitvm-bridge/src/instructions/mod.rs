@@ -2,6 +2,7 @@ pub mod admin;
 pub mod burn;
 pub mod initialize;
 pub mod lp;
+pub mod migrate;
 pub mod mint;
 pub mod transfer;
 
@@ -9,5 +10,6 @@ pub use admin::*;
 pub use burn::*;
 pub use initialize::*;
 pub use lp::*;
+pub use migrate::*;
 pub use mint::*;
 pub use transfer::*;
@@ -27,6 +27,14 @@ pub mod bitvm_bridge {
         mint::mint_token(ctx, tx_id, amount)
     }
 
+    // Permissionless mint of a verified deposit to the recipient its transaction committed to
+    pub fn mint_to_committed_recipient(
+        ctx: Context<MintToCommittedRecipient>,
+        tx_id: [u8; 32],
+    ) -> Result<()> {
+        mint::mint_to_committed_recipient(ctx, tx_id)
+    }
+
     pub fn burn(
         ctx: Context<BurnToken>,
         amount: u64,
@@ -77,6 +85,23 @@ pub mod bitvm_bridge {
         admin::set_lp_withdraw_timeout(ctx, timeout)
     }
 
+    pub fn set_deposit_script_hash(
+        ctx: Context<SetDepositScriptHash>,
+        deposit_script_hash: [u8; 32],
+    ) -> Result<()> {
+        admin::set_deposit_script_hash(ctx, deposit_script_hash)
+    }
+
+    // Grows a bridge state deployed before the deposit script hash existed
+    pub fn migrate_bridge_state(ctx: Context<MigrateBridgeState>) -> Result<()> {
+        migrate::migrate_bridge_state(ctx)
+    }
+
+    // Hands the mint authority of a bridge deployed with the owner as minter to the bridge state
+    pub fn hand_over_mint_authority(ctx: Context<HandOverMintAuthority>) -> Result<()> {
+        admin::hand_over_mint_authority(ctx)
+    }
+
     // LP Management Functions
     pub fn register_lp(ctx: Context<RegisterLP>, lp_register: LPRegister) -> Result<()> {
         lp::register_lp(ctx, lp_register)
@@ -90,6 +115,7 @@ pub mod bitvm_bridge {
         lp::update_lp_status(ctx, lp_id, new_status)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn withdraw_by_lp(
         ctx: Context<WithdrawByLP>,
         withdraw_id: u64,
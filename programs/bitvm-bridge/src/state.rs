@@ -37,6 +37,49 @@ pub struct BridgeState {
     pub burn_paused: bool,
     pub max_fee_rate: u64,
     pub lp_withdraw_timeout: u64,
+    /// SHA-256 of the script_pubkey pegin deposits must pay to
+    pub deposit_script_hash: [u8; 32],
+}
+
+impl BridgeState {
+    pub const SPACE: usize = 8 + std::mem::size_of::<BridgeState>();
+}
+
+/// Bridge state as deployed before `deposit_script_hash` was appended. Accounts were
+/// allocated from the struct size, so they are too short for the current layout until
+/// `migrate_bridge_state` grows them.
+#[derive(AnchorDeserialize)]
+pub struct LegacyBridgeState {
+    pub owner: Pubkey,
+    pub mint_account: Pubkey,
+    pub max_btc_per_mint: u64,
+    pub min_btc_per_mint: u64,
+    pub max_btc_per_burn: u64,
+    pub min_btc_per_burn: u64,
+    pub skip_tx_verification: bool,
+    pub burn_paused: bool,
+    pub max_fee_rate: u64,
+    pub lp_withdraw_timeout: u64,
+}
+
+impl LegacyBridgeState {
+    pub const SPACE: usize = 8 + std::mem::size_of::<LegacyBridgeState>();
+
+    /// Reads the state from account data in the legacy layout, which kept the type name and
+    /// so the discriminator of `BridgeState`
+    pub fn try_from_account_data(data: &[u8]) -> Result<Self> {
+        require!(
+            data.len() == Self::SPACE,
+            ErrorCode::AccountDidNotDeserialize
+        );
+        require!(
+            data.starts_with(BridgeState::DISCRIMINATOR),
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        Ok(Self::try_from_slice(
+            &data[BridgeState::DISCRIMINATOR.len()..],
+        )?)
+    }
 }
 
 #[account]
@@ -48,6 +91,16 @@ impl TxMintedState {
     pub const SPACE: usize = 8 + 1; // discriminator + is_minted
 }
 
+/// Marks a Bitcoin transaction that already backed an LP withdraw claim
+#[account]
+pub struct TxClaimedState {
+    pub is_claimed: bool,
+}
+
+impl TxClaimedState {
+    pub const SPACE: usize = 8 + 1; // discriminator + is_claimed
+}
+
 #[account]
 pub struct LPState {
     pub id: u64,
@@ -0,0 +1,365 @@
+use crate::{
+    errors::BtcLightClientError,
+    events::{ChainReorg, NewTip},
+    header::RawHeader,
+    state::{BlockHashEntry, BtcLightClientState, HeaderStore, Network, StoredHeader},
+    utils::{
+        calculate_next_work_required, get_and_verify_block_hash_account, median_time_past,
+        push_block_time, DIFFICULTY_ADJUSTMENT_INTERVAL, MAX_FUTURE_BLOCK_TIME, MAX_TIMEWARP,
+        MEDIAN_TIME_SPAN, POW_TARGET_SPACING,
+    },
+};
+use anchor_lang::prelude::*;
+use bitcoin::{
+    hashes::Hash,
+    pow::{CompactTarget, Target, Work},
+    BlockHash,
+};
+
+/// Storage the verified header chain is written to
+pub trait HeaderSink {
+    /// Stores the entry of the `index`-th submitted header and returns the hash previously
+    /// recorded at its height, or zeros if there was none
+    fn store(&mut self, index: usize, entry: &BlockHashEntry) -> Result<[u8; 32]>;
+}
+
+/// Writes headers into the per-height `BlockHashEntry` accounts passed alongside them
+pub struct BlockHashEntrySink<'a, 'info> {
+    pub accounts: &'a [AccountInfo<'info>],
+    pub program_id: &'a Pubkey,
+}
+
+impl HeaderSink for BlockHashEntrySink<'_, '_> {
+    fn store(&mut self, index: usize, entry: &BlockHashEntry) -> Result<[u8; 32]> {
+        let account_info = self
+            .accounts
+            .get(index)
+            .ok_or(BtcLightClientError::InvalidAccountNumber)?;
+        let previous =
+            get_and_verify_block_hash_account(account_info, entry.height, self.program_id)?;
+        // Keep the rent payer recorded when the account was created
+        BlockHashEntry {
+            payer: previous.payer,
+            ..entry.clone()
+        }
+        .try_serialize(&mut &mut account_info.data.borrow_mut()[..])?;
+        Ok(previous.hash)
+    }
+}
+
+impl HeaderSink for HeaderStore {
+    fn store(&mut self, _index: usize, entry: &BlockHashEntry) -> Result<[u8; 32]> {
+        Ok(self.put(entry))
+    }
+}
+
+/// Parent of a submitted branch: a verified block on the current chain, no deeper below the
+/// tip than a reorg may reach. Its entry carries the difficulty period it is in, so the branch
+/// may start in an earlier period than the tip.
+pub fn branch_parent(state: &BtcLightClientState, parent: &BlockHashEntry) -> Result<StoredHeader> {
+    require!(
+        parent.hash != [0; 32] && parent.height <= state.latest_block_height,
+        BtcLightClientError::ParentBlockNotYetSubmitted
+    );
+    require!(
+        state.max_reorg_depth == 0
+            || state.latest_block_height - parent.height <= state.max_reorg_depth,
+        BtcLightClientError::ReorgTooDeep
+    );
+    Ok(StoredHeader::from(parent))
+}
+
+impl StoredHeader {
+    /// Validates `header` as the child of this block under the rules of `network` and
+    /// advances to it
+    pub fn extend(
+        &mut self,
+        network: Network,
+        header: &RawHeader,
+        max_block_time: i64,
+    ) -> Result<StoredHeader> {
+        let pow_limit = network.pow_limit();
+        let current_height = self.height + 1;
+        let hash_bytes = header.block_hash();
+        let time = header.time();
+        let mut block_times = self.recent_block_times;
+
+        // Verify previous block hash
+        require!(
+            *header.prev_blockhash() == self.hash,
+            BtcLightClientError::InvalidPrevHash
+        );
+
+        // Verify timestamp against median time past and the current time
+        require!(
+            time > median_time_past(&block_times),
+            BtcLightClientError::BlockTimeTooOld
+        );
+        require!(
+            time as i64 <= max_block_time,
+            BtcLightClientError::BlockTimeTooNew
+        );
+
+        // Verify PoW and difficulty
+        let target = header.target();
+        require!(
+            target <= pow_limit && target.is_met_by(BlockHash::from_byte_array(hash_bytes)),
+            BtcLightClientError::InvalidProofOfWork
+        );
+
+        let prev_time = block_times[MEDIAN_TIME_SPAN - 1];
+        let prev_bits = self.bits;
+        let is_retarget = current_height.is_multiple_of(DIFFICULTY_ADJUSTMENT_INTERVAL);
+        let expected_bits = if is_retarget {
+            // BIP94: the first block of a period may not predate its parent by more than ten minutes
+            if network == Network::Testnet4 {
+                require!(
+                    time >= prev_time.saturating_sub(MAX_TIMEWARP),
+                    BtcLightClientError::BlockTimeTooOld
+                );
+            }
+            if network.no_retargeting() {
+                CompactTarget::from_consensus(prev_bits)
+            } else {
+                // Testnet3 scales the previous block's target, which may be a minimum difficulty one
+                let base_target = if network == Network::Testnet3 {
+                    Target::from_compact(CompactTarget::from_consensus(prev_bits)).to_be_bytes()
+                } else {
+                    self.period_target
+                };
+                calculate_next_work_required(
+                    &base_target,
+                    self.period_start_time,
+                    prev_time,
+                    pow_limit,
+                )
+            }
+        } else if network.allows_min_difficulty_blocks()
+            && time as u64 > prev_time as u64 + 2 * POW_TARGET_SPACING as u64
+        {
+            // A block more than twenty minutes after its parent must use minimum difficulty
+            pow_limit.to_compact_lossy()
+        } else {
+            Target::from_be_bytes(self.period_target).to_compact_lossy()
+        };
+        require!(
+            header.bits() == expected_bits.to_consensus(),
+            BtcLightClientError::InvalidDifficultyAdjustment
+        );
+
+        let (period_target, period_start_time) = if is_retarget {
+            (target.to_be_bytes(), time)
+        } else {
+            (self.period_target, self.period_start_time)
+        };
+        push_block_time(&mut block_times, time);
+        let chainwork = Work::from_be_bytes(self.chainwork) + target.to_work();
+
+        *self = StoredHeader {
+            height: current_height,
+            hash: hash_bytes,
+            chainwork: chainwork.to_be_bytes(),
+            recent_block_times: block_times,
+            bits: header.bits(),
+            period_start_time,
+            period_target,
+            _padding: [0; 4],
+        };
+        Ok(*self)
+    }
+}
+
+/// Latest timestamp a header may carry, two hours past the cluster clock
+pub fn max_block_time() -> Result<i64> {
+    Ok(Clock::get()?.unix_timestamp + MAX_FUTURE_BLOCK_TIME)
+}
+
+/// Validates `headers` as a chain building on `parent` and, if it ends up heavier than the
+/// current tip, stores it and moves the tip. Returns the number of headers that changed the
+/// stored chain.
+pub fn append_headers<S: HeaderSink>(
+    state: &mut BtcLightClientState,
+    parent: &BlockHashEntry,
+    headers: &[RawHeader],
+    sink: &mut S,
+) -> Result<u64> {
+    require!(!headers.is_empty(), BtcLightClientError::NoHeaders);
+
+    let mut tip = branch_parent(state, parent)?;
+    let network = state.network;
+    let max_block_time = max_block_time()?;
+    let branch = headers
+        .iter()
+        .map(|header| tip.extend(network, header, max_block_time))
+        .collect::<Result<Vec<_>>>()?;
+
+    apply_branch(state, branch, &tip, sink)
+}
+
+/// Stores `branch`, a validated chain of headers ending in `tip`, and moves the chain tip to it
+/// if it carries more work than the current one. Returns the number of headers that changed
+/// the stored chain.
+pub fn apply_branch<S: HeaderSink>(
+    state: &mut BtcLightClientState,
+    branch: impl IntoIterator<Item = StoredHeader>,
+    tip: &StoredHeader,
+    sink: &mut S,
+) -> Result<u64> {
+    let old_height = state.latest_block_height;
+    let mut fork_height = None;
+    let mut accepted_headers = 0u64;
+
+    for (i, header) in branch.into_iter().enumerate() {
+        let current_height = header.height;
+        let previous_hash = sink.store(i, &BlockHashEntry::from(&header))?;
+
+        // The pinned block is the only one accepted at its height
+        require!(
+            current_height != state.checkpoint_height || header.hash == state.checkpoint_hash,
+            BtcLightClientError::CheckpointConflict
+        );
+
+        // The first differing block at or below the old tip marks where the branches split
+        if fork_height.is_none() && current_height <= old_height && previous_hash != header.hash {
+            require!(
+                current_height > state.checkpoint_height,
+                BtcLightClientError::CheckpointConflict
+            );
+            require!(
+                state.max_reorg_depth == 0
+                    || old_height - (current_height - 1) <= state.max_reorg_depth,
+                BtcLightClientError::ReorgTooDeep
+            );
+            fork_height = Some(current_height - 1);
+        }
+        if current_height > old_height || previous_hash != header.hash {
+            accepted_headers += 1;
+        }
+    }
+
+    // Heaviest chain wins: the new tip must carry more cumulative work than the current one
+    require!(
+        Work::from_be_bytes(tip.chainwork) > Work::from_be_bytes(state.latest_chainwork),
+        BtcLightClientError::InsufficientChainWork
+    );
+
+    let new_tip = tip.hash;
+    if let Some(fork_height) = fork_height {
+        emit!(ChainReorg {
+            reorg_count: old_height - fork_height,
+            fork_height,
+            old_tip: state.latest_block_hash,
+            new_tip,
+        });
+    }
+
+    state.latest_block_height = tip.height;
+    state.latest_block_hash = new_tip;
+    state.latest_block_time = tip.recent_block_times[MEDIAN_TIME_SPAN - 1];
+    state.latest_period_target = tip.period_target;
+    state.period_start_time = tip.period_start_time;
+    state.latest_chainwork = tip.chainwork;
+    state.recent_block_times = tip.recent_block_times;
+
+    emit!(NewTip {
+        block_height: state.latest_block_height,
+        block_time: state.latest_block_time,
+        block_hash: new_tip,
+    });
+
+    Ok(accepted_headers)
+}
+
+/// Entry of the verified block at `height`, read from the header store once one is attached,
+/// otherwise from `block_hash_entry`
+pub fn load_entry(
+    state: &BtcLightClientState,
+    height: u64,
+    block_hash_entry: Option<&BlockHashEntry>,
+    header_store: Option<&AccountLoader<HeaderStore>>,
+) -> Result<BlockHashEntry> {
+    if state.header_store != Pubkey::default() {
+        header_store
+            .ok_or(BtcLightClientError::InvalidHeaderStore)?
+            .load()?
+            .get(height)
+            .ok_or(error!(BtcLightClientError::ParentBlockNotYetSubmitted))
+    } else {
+        block_hash_entry
+            .cloned()
+            .ok_or(error!(BtcLightClientError::ParentBlockNotYetSubmitted))
+    }
+}
+
+/// Appends `headers` starting at `block_height` to whichever storage the light client uses:
+/// the header store once attached, otherwise the parent entry plus one `BlockHashEntry` per
+/// header in `remaining_accounts`
+pub fn store_headers(
+    state: &mut BtcLightClientState,
+    block_height: u64,
+    headers: &[RawHeader],
+    parent_block_hash_entry: Option<&BlockHashEntry>,
+    header_store: Option<&AccountLoader<HeaderStore>>,
+    remaining_accounts: &[AccountInfo],
+    program_id: &Pubkey,
+) -> Result<u64> {
+    let parent_height = block_height
+        .checked_sub(1)
+        .ok_or(BtcLightClientError::ParentBlockNotYetSubmitted)?;
+    let parent = load_entry(state, parent_height, parent_block_hash_entry, header_store)?;
+
+    if state.header_store != Pubkey::default() {
+        // Headers go to the ring buffer, no per-height accounts are needed
+        let mut header_store = header_store
+            .ok_or(BtcLightClientError::InvalidHeaderStore)?
+            .load_mut()?;
+        append_headers(state, &parent, headers, &mut *header_store)
+    } else {
+        require!(
+            remaining_accounts.len() == headers.len(),
+            BtcLightClientError::InvalidAccountNumber
+        );
+        let mut sink = BlockHashEntrySink {
+            accounts: remaining_accounts,
+            program_id,
+        };
+        append_headers(state, &parent, headers, &mut sink)
+    }
+}
+
+/// Whether the verified block at `height` can no longer be replaced: it is at or below the
+/// checkpoint, or at least as deep below the tip as the deepest reorg may fork from
+pub fn is_final(state: &BtcLightClientState, height: u64) -> bool {
+    height <= state.checkpoint_height
+        || (state.max_reorg_depth != 0
+            && state.latest_block_height.saturating_sub(height) >= state.max_reorg_depth)
+}
+
+/// Hash of the verified block at `height`. Once a header store is attached, blocks above its
+/// base height are only read from the store.
+pub fn canonical_block_hash(
+    state: &BtcLightClientState,
+    height: u64,
+    block_hash_entry: Option<&BlockHashEntry>,
+    header_store: Option<&HeaderStore>,
+) -> Result<[u8; 32]> {
+    require!(
+        height <= state.latest_block_height,
+        BtcLightClientError::BlockNotStored
+    );
+
+    if state.header_store == Pubkey::default() {
+        return block_hash_entry
+            .map(|entry| entry.hash)
+            .ok_or(error!(BtcLightClientError::BlockNotStored));
+    }
+
+    let header_store = header_store.ok_or(BtcLightClientError::InvalidHeaderStore)?;
+    if let Some(entry) = header_store.get(height) {
+        return Ok(entry.hash);
+    }
+    match block_hash_entry {
+        Some(entry) if height <= header_store.base_height => Ok(entry.hash),
+        _ => err!(BtcLightClientError::BlockNotStored),
+    }
+}
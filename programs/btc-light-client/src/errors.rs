@@ -44,4 +44,88 @@ pub enum BtcLightClientError {
     EmptyPdaAccount,
     #[msg("Unauthorized signer")]
     UnauthorizedSigner,
+    #[msg("Insufficient chain work")]
+    InsufficientChainWork,
+    #[msg("Block time not above median time past")]
+    BlockTimeTooOld,
+    #[msg("Block time too far in the future")]
+    BlockTimeTooNew,
+    #[msg("Invalid header store account")]
+    InvalidHeaderStore,
+    #[msg("Header store already initialized")]
+    HeaderStoreAlreadyInitialized,
+    #[msg("Block not stored")]
+    BlockNotStored,
+    #[msg("Rent payer mismatch")]
+    RentPayerMismatch,
+    #[msg("Block entry within retention depth")]
+    BlockEntryRetained,
+    #[msg("Light client is not following signet")]
+    NotSignet,
+    #[msg("Unsupported signet challenge")]
+    UnsupportedSignetChallenge,
+    #[msg("Invalid signet block solution")]
+    InvalidSignetSolution,
+    #[msg("Signet headers need a block solution")]
+    SignetSolutionRequired,
+    #[msg("Invalid witness commitment")]
+    InvalidWitnessCommitment,
+    #[msg("Invalid coinbase proof")]
+    InvalidCoinbaseProof,
+    #[msg("Coinbase proof required")]
+    CoinbaseProofRequired,
+    #[msg("Merkle proof depth does not match the coinbase proof")]
+    InvalidMerkleProofDepth,
+    #[msg("64-byte transactions are not accepted")]
+    AmbiguousTransactionSize,
+    #[msg("Invalid expected outputs")]
+    InvalidExpectedOutputs,
+    #[msg("Invalid recipient commitment")]
+    InvalidRecipientCommitment,
+    #[msg("Invalid input index")]
+    InvalidInputIndex,
+    #[msg("Input does not spend the expected outpoint")]
+    OutpointMismatch,
+    #[msg("Input sequence mismatch")]
+    SequenceMismatch,
+    #[msg("Lock time mismatch")]
+    LockTimeMismatch,
+    #[msg("Submitter already allowed")]
+    SubmitterAlreadyAllowed,
+    #[msg("Submitter not allowed")]
+    SubmitterNotAllowed,
+    #[msg("Submitter allowlist full")]
+    SubmitterAllowlistFull,
+    #[msg("Signer is not the pending owner")]
+    NotPendingOwner,
+    #[msg("Empty transaction chunk")]
+    EmptyTxChunk,
+    #[msg("Invalid partial transaction proof")]
+    InvalidPartialTxProof,
+    #[msg("Reorg exceeds the maximum depth")]
+    ReorgTooDeep,
+    #[msg("Headers conflict with a checkpoint")]
+    CheckpointConflict,
+    #[msg("Invalid checkpoint")]
+    InvalidCheckpoint,
+    #[msg("Header submission paused")]
+    SubmissionPaused,
+    #[msg("Header submission already paused")]
+    SubmissionAlreadyPaused,
+    #[msg("Header submission not paused")]
+    SubmissionNotPaused,
+    #[msg("Transaction verification paused")]
+    VerificationPaused,
+    #[msg("Transaction verification already paused")]
+    VerificationAlreadyPaused,
+    #[msg("Transaction verification not paused")]
+    VerificationNotPaused,
+    #[msg("State already at the current version")]
+    StateUpToDate,
+    #[msg("State layout is newer than this program")]
+    UnsupportedStateVersion,
+    #[msg("Expected outputs must include every output paying the same script")]
+    IncompleteScriptOutputs,
+    #[msg("Migrated data does not match the light client state")]
+    MigrationMismatch,
 }
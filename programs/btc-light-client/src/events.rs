@@ -14,9 +14,26 @@ pub struct TransactionVerified {
     pub amount: u64,
 }
 
+#[event]
+pub struct TransactionWitnessVerified {
+    pub block_height: u64,
+    pub tx_id: [u8; 32],
+    pub wtxid: [u8; 32],
+    pub amount: u64,
+}
+
+#[event]
+pub struct SpendVerified {
+    pub block_height: u64,
+    pub spent_tx_id: [u8; 32],
+    pub spent_vout: u32,
+    pub spending_tx_id: [u8; 32],
+}
+
 #[event]
 pub struct ChainReorg {
     pub reorg_count: u64,
+    pub fork_height: u64,
     pub old_tip: [u8; 32],
     pub new_tip: [u8; 32],
 }
@@ -33,3 +50,112 @@ pub struct MinConfirmationsUpdated {
     pub new_value: u64,
     pub authority: Pubkey,
 }
+
+#[event]
+pub struct PermissionlessRelayingUpdated {
+    pub enabled: bool,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct RequireCoinbaseProofUpdated {
+    pub enabled: bool,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct RelayerRewardUpdated {
+    pub old_value: u64,
+    pub new_value: u64,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct RelayerRewardsFunded {
+    pub funder: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct RelayerRewardPaid {
+    pub relayer: Pubkey,
+    pub accepted_headers: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct BlockEntryRetentionUpdated {
+    pub old_value: u64,
+    pub new_value: u64,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct TxConsumerUpdated {
+    pub old_value: Pubkey,
+    pub new_value: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct SignetChallengeSet {
+    pub challenge: Vec<u8>,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct SubmitterAdded {
+    pub submitter: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct SubmitterRemoved {
+    pub submitter: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct OwnershipTransferProposed {
+    pub current_owner: Pubkey,
+    pub pending_owner: Pubkey,
+}
+
+#[event]
+pub struct OwnershipTransferred {
+    pub previous_owner: Pubkey,
+    pub new_owner: Pubkey,
+}
+
+#[event]
+pub struct MaxReorgDepthUpdated {
+    pub old_value: u64,
+    pub new_value: u64,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct CheckpointSet {
+    pub block_height: u64,
+    pub block_hash: [u8; 32],
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct SubmissionPauseUpdated {
+    pub paused: bool,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct VerificationPauseUpdated {
+    pub paused: bool,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct StateMigrated {
+    pub old_version: u8,
+    pub new_version: u8,
+    pub authority: Pubkey,
+}
@@ -0,0 +1,62 @@
+use crate::{errors::BtcLightClientError, utils::sha256d};
+use anchor_lang::prelude::*;
+use bitcoin::pow::{CompactTarget, Target};
+
+/// Size of a serialized block header
+pub const HEADER_SIZE: usize = 80;
+
+/// A block header read in place from its 80-byte serialization, so submitting headers
+/// neither copies nor decodes them. All fields are little-endian.
+#[derive(Clone, Copy)]
+pub struct RawHeader<'a>(&'a [u8; HEADER_SIZE]);
+
+impl<'a> RawHeader<'a> {
+    pub fn parse(bytes: &'a [u8]) -> Result<Self> {
+        let bytes = bytes
+            .try_into()
+            .map_err(|_| BtcLightClientError::InvalidHeader)?;
+        Ok(RawHeader(bytes))
+    }
+
+    /// Splits raw bytes into consecutive 80-byte headers
+    pub fn parse_all(bytes: &'a [u8]) -> Result<Vec<Self>> {
+        let headers = bytes.chunks_exact(HEADER_SIZE);
+        require!(
+            headers.remainder().is_empty(),
+            BtcLightClientError::InvalidHeader
+        );
+        Ok(headers
+            .map(|header| RawHeader(header.try_into().unwrap()))
+            .collect())
+    }
+
+    pub fn as_bytes(&self) -> &'a [u8; HEADER_SIZE] {
+        self.0
+    }
+
+    pub fn prev_blockhash(&self) -> &'a [u8; 32] {
+        self.0[4..36].try_into().unwrap()
+    }
+
+    pub fn merkle_root(&self) -> &'a [u8; 32] {
+        self.0[36..68].try_into().unwrap()
+    }
+
+    pub fn time(&self) -> u32 {
+        u32::from_le_bytes(self.0[68..72].try_into().unwrap())
+    }
+
+    /// Compact difficulty target
+    pub fn bits(&self) -> u32 {
+        u32::from_le_bytes(self.0[72..76].try_into().unwrap())
+    }
+
+    pub fn target(&self) -> Target {
+        Target::from_compact(CompactTarget::from_consensus(self.bits()))
+    }
+
+    /// Double SHA-256 of the header, through the `sol_sha256` syscall
+    pub fn block_hash(&self) -> [u8; 32] {
+        sha256d(&[self.0])
+    }
+}
@@ -0,0 +1,37 @@
+use crate::errors::BtcLightClientError;
+use crate::events::OwnershipTransferred;
+use crate::state::BtcLightClientState;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct AcceptOwnership<'info> {
+    /// The BTC Light Client state account
+    #[account(
+        mut,
+        seeds = [b"btc_light_client"],
+        bump,
+        // ensure the caller is the proposed owner
+        constraint = state.pending_owner != Pubkey::default()
+            && state.pending_owner == new_owner.key() @ BtcLightClientError::NotPendingOwner
+    )]
+    pub state: Account<'info, BtcLightClientState>,
+
+    /// The account proposed by the current owner
+    #[account(mut)]
+    pub new_owner: Signer<'info>,
+}
+
+pub fn accept_ownership(ctx: Context<AcceptOwnership>) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    let previous_owner = state.owner;
+    state.owner = state.pending_owner;
+    state.pending_owner = Pubkey::default();
+
+    // emit event
+    emit!(OwnershipTransferred {
+        previous_owner,
+        new_owner: state.owner,
+    });
+
+    Ok(())
+}
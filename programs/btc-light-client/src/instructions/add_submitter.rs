@@ -0,0 +1,52 @@
+use crate::errors::BtcLightClientError;
+use crate::events::SubmitterAdded;
+use crate::state::{BtcLightClientState, SubmitterAllowlist, MAX_SUBMITTERS};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct AddSubmitter<'info> {
+    /// The BTC Light Client state account
+    #[account(
+        seeds = [b"btc_light_client"],
+        bump,
+        // ensure the caller is the owner
+        constraint = state.owner == authority.key() @ BtcLightClientError::UnauthorizedSigner
+    )]
+    pub state: Account<'info, BtcLightClientState>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = SubmitterAllowlist::SPACE,
+        seeds = [b"submitter_allowlist"],
+        bump
+    )]
+    pub submitter_allowlist: Account<'info, SubmitterAllowlist>,
+
+    /// The authority that can allow submitters (must be owner)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn add_submitter(ctx: Context<AddSubmitter>, submitter: Pubkey) -> Result<()> {
+    let submitters = &mut ctx.accounts.submitter_allowlist.submitters;
+    require!(
+        !submitters.contains(&submitter),
+        BtcLightClientError::SubmitterAlreadyAllowed
+    );
+    require!(
+        submitters.len() < MAX_SUBMITTERS,
+        BtcLightClientError::SubmitterAllowlistFull
+    );
+    submitters.push(submitter);
+
+    // emit event
+    emit!(SubmitterAdded {
+        submitter,
+        authority: ctx.accounts.authority.key(),
+    });
+
+    Ok(())
+}
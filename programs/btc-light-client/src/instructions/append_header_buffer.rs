@@ -0,0 +1,64 @@
+use crate::{
+    chain::max_block_time, errors::BtcLightClientError, header::RawHeader, state::*,
+    utils::realloc_account,
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct AppendHeaderBuffer<'info> {
+    #[account(seeds = [b"btc_light_client"], bump)]
+    pub state: Account<'info, BtcLightClientState>,
+
+    #[account(
+        mut,
+        seeds = [b"header_buffer", submitter.key().as_ref()],
+        bump
+    )]
+    pub header_buffer: AccountLoader<'info, HeaderBuffer>,
+
+    #[account(mut)]
+    pub submitter: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Each chunk is validated against the last buffered header and stored as `StoredHeader`
+// records, so finalizing only has to write them out
+pub fn append_header_buffer(ctx: Context<AppendHeaderBuffer>, headers: Vec<u8>) -> Result<()> {
+    let headers = RawHeader::parse_all(&headers)?;
+    require!(!headers.is_empty(), BtcLightClientError::NoHeaders);
+
+    let (mut tip, count) = {
+        let header_buffer = ctx.accounts.header_buffer.load()?;
+        (header_buffer.tip, header_buffer.count as usize)
+    };
+
+    let network = ctx.accounts.state.network;
+    let max_block_time = max_block_time()?;
+    let buffered = headers
+        .iter()
+        .map(|header| tip.extend(network, header, max_block_time))
+        .collect::<Result<Vec<_>>>()?;
+
+    let buffer_info = ctx.accounts.header_buffer.to_account_info();
+    realloc_account(
+        &buffer_info,
+        &ctx.accounts.submitter.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        HeaderBuffer::space(count + buffered.len()),
+    )?;
+    {
+        let mut data = buffer_info.try_borrow_mut_data()?;
+        let records = &mut data[HeaderBuffer::space(count)..];
+        for (record, header) in records
+            .chunks_exact_mut(std::mem::size_of::<StoredHeader>())
+            .zip(&buffered)
+        {
+            record.copy_from_slice(bytemuck::bytes_of(header));
+        }
+    }
+
+    let mut header_buffer = ctx.accounts.header_buffer.load_mut()?;
+    header_buffer.count += buffered.len() as u64;
+    header_buffer.tip = tip;
+    Ok(())
+}
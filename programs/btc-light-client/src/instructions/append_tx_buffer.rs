@@ -0,0 +1,43 @@
+use crate::{errors::BtcLightClientError, state::TxBuffer, utils::realloc_account};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(tx_id: [u8; 32])]
+pub struct AppendTxBuffer<'info> {
+    #[account(
+        mut,
+        seeds = [b"tx_buffer", payer.key().as_ref(), tx_id.as_ref()],
+        bump
+    )]
+    pub tx_buffer: AccountLoader<'info, TxBuffer>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Writes `chunk` at `offset` of the raw transaction, growing the buffer to fit. Chunks may
+// land in any order or be resent; the data is only checked against the txid when the buffer
+// is verified.
+pub fn append_tx_buffer(
+    ctx: Context<AppendTxBuffer>,
+    _tx_id: [u8; 32],
+    offset: u32,
+    chunk: Vec<u8>,
+) -> Result<()> {
+    require!(!chunk.is_empty(), BtcLightClientError::EmptyTxChunk);
+
+    let tx_buffer = ctx.accounts.tx_buffer.to_account_info();
+    let start = TxBuffer::space(offset as usize);
+    let end = start + chunk.len();
+    if end > tx_buffer.data_len() {
+        realloc_account(
+            &tx_buffer,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            end,
+        )?;
+    }
+    tx_buffer.try_borrow_mut_data()?[start..end].copy_from_slice(&chunk);
+    Ok(())
+}
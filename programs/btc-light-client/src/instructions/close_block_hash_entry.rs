@@ -0,0 +1,35 @@
+use crate::{errors::BtcLightClientError, state::*};
+use anchor_lang::prelude::*;
+
+// Anyone can close stale entries; the rent always goes back to whoever paid for them
+#[derive(Accounts)]
+#[instruction(height: u64)]
+pub struct CloseBlockHashEntry<'info> {
+    #[account(seeds = [b"btc_light_client"], bump)]
+    pub state: Account<'info, BtcLightClientState>,
+
+    #[account(
+        mut,
+        seeds = [b"block_hash_entry".as_ref(), height.to_le_bytes().as_ref()],
+        bump,
+        close = payer,
+        constraint = block_hash_entry.payer == payer.key() @ BtcLightClientError::RentPayerMismatch
+    )]
+    pub block_hash_entry: Account<'info, BlockHashEntry>,
+
+    /// CHECK: receives the rent, checked against the payer recorded in the entry
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+}
+
+pub fn close_block_hash_entry(ctx: Context<CloseBlockHashEntry>, height: u64) -> Result<()> {
+    let state = &ctx.accounts.state;
+
+    require!(
+        state.block_entry_retention > 0
+            && height.saturating_add(state.block_entry_retention) < state.latest_block_height,
+        BtcLightClientError::BlockEntryRetained
+    );
+
+    Ok(())
+}
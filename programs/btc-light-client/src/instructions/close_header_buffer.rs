@@ -0,0 +1,21 @@
+use crate::state::HeaderBuffer;
+use anchor_lang::prelude::*;
+
+// Discards an upload that will not be finalized, refunding its rent
+#[derive(Accounts)]
+pub struct CloseHeaderBuffer<'info> {
+    #[account(
+        mut,
+        seeds = [b"header_buffer", submitter.key().as_ref()],
+        bump,
+        close = submitter
+    )]
+    pub header_buffer: AccountLoader<'info, HeaderBuffer>,
+
+    #[account(mut)]
+    pub submitter: Signer<'info>,
+}
+
+pub fn close_header_buffer(_ctx: Context<CloseHeaderBuffer>) -> Result<()> {
+    Ok(())
+}
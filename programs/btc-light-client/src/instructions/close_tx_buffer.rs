@@ -0,0 +1,22 @@
+use crate::state::TxBuffer;
+use anchor_lang::prelude::*;
+
+// Discards an upload that will not be verified, refunding its rent
+#[derive(Accounts)]
+#[instruction(tx_id: [u8; 32])]
+pub struct CloseTxBuffer<'info> {
+    #[account(
+        mut,
+        seeds = [b"tx_buffer", payer.key().as_ref(), tx_id.as_ref()],
+        bump,
+        close = payer
+    )]
+    pub tx_buffer: AccountLoader<'info, TxBuffer>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+pub fn close_tx_buffer(_ctx: Context<CloseTxBuffer>, _tx_id: [u8; 32]) -> Result<()> {
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use crate::{errors::BtcLightClientError, state::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(tx_id: [u8; 32])]
+pub struct CloseTxVerifiedState<'info> {
+    #[account(
+        seeds = [b"btc_light_client"],
+        bump,
+        constraint = state.tx_consumer == consumer.key() @ BtcLightClientError::UnauthorizedSigner
+    )]
+    pub state: Account<'info, BtcLightClientState>,
+
+    #[account(
+        mut,
+        seeds = [b"tx_verified_state".as_ref(), tx_id.as_ref()],
+        bump,
+        close = payer,
+        constraint = tx_verified_state.payer == payer.key() @ BtcLightClientError::RentPayerMismatch
+    )]
+    pub tx_verified_state: Account<'info, TxVerifiedState>,
+
+    /// CHECK: receives the rent, checked against the payer recorded in the state
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+
+    /// The consumer of verified transactions, usually a PDA of the bridge program
+    pub consumer: Signer<'info>,
+}
+
+pub fn close_tx_verified_state(
+    _ctx: Context<CloseTxVerifiedState>,
+    _tx_id: [u8; 32],
+) -> Result<()> {
+    Ok(())
+}
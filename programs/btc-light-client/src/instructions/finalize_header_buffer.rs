@@ -0,0 +1,131 @@
+use crate::{
+    chain::{apply_branch, branch_parent, load_entry, BlockHashEntrySink},
+    errors::BtcLightClientError,
+    instructions::submit_headers::pay_relayer_reward,
+    state::*,
+    utils::get_and_verify_block_hash_account,
+};
+use anchor_lang::prelude::*;
+
+// Applies the buffered headers exactly like a single `submit_block_headers` call, so a long
+// branch either replaces the chain as a whole or not at all. The headers were validated when
+// appended; only the parent is checked again, as the chain may have moved since. Without a
+// header store every buffered header needs its `BlockHashEntry` in the remaining accounts, so
+// branches longer than one transaction can reference need the header store.
+pub fn finalize_header_buffer(ctx: Context<FinalizeHeaderBuffer>) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    // Signet headers go through `submit_signet_header` along with their block solution
+    require!(
+        state.network != Network::Signet,
+        BtcLightClientError::SignetSolutionRequired
+    );
+
+    let (start_height, count, parent_hash, tip) = {
+        let header_buffer = ctx.accounts.header_buffer.load()?;
+        (
+            header_buffer.start_height,
+            header_buffer.count as usize,
+            header_buffer.parent_hash,
+            header_buffer.tip,
+        )
+    };
+    require!(count > 0, BtcLightClientError::NoHeaders);
+
+    // The parent must still be the block the headers were validated against
+    let parent_height = start_height - 1;
+    let parent_block_hash_entry = match &ctx.accounts.parent_block_hash_entry {
+        Some(account) if state.header_store == Pubkey::default() => Some(
+            get_and_verify_block_hash_account(account, parent_height, ctx.program_id)?,
+        ),
+        _ => None,
+    };
+    let parent = load_entry(
+        state,
+        parent_height,
+        parent_block_hash_entry.as_ref(),
+        ctx.accounts.header_store.as_ref(),
+    )?;
+    require!(
+        parent.hash == parent_hash,
+        BtcLightClientError::InvalidPrevHash
+    );
+    branch_parent(state, &parent)?;
+
+    let buffer_info = ctx.accounts.header_buffer.to_account_info();
+    let data = buffer_info.try_borrow_data()?;
+    let branch = data[HeaderBuffer::space(0)..HeaderBuffer::space(count)]
+        .chunks_exact(std::mem::size_of::<StoredHeader>())
+        .map(bytemuck::pod_read_unaligned::<StoredHeader>);
+
+    let accepted_headers = if state.header_store != Pubkey::default() {
+        let mut header_store = ctx
+            .accounts
+            .header_store
+            .as_ref()
+            .ok_or(BtcLightClientError::InvalidHeaderStore)?
+            .load_mut()?;
+        apply_branch(state, branch, &tip, &mut *header_store)?
+    } else {
+        require!(
+            ctx.remaining_accounts.len() == count,
+            BtcLightClientError::InvalidAccountNumber
+        );
+        let mut sink = BlockHashEntrySink {
+            accounts: ctx.remaining_accounts,
+            program_id: ctx.program_id,
+        };
+        apply_branch(state, branch, &tip, &mut sink)?
+    };
+    drop(data);
+
+    pay_relayer_reward(
+        &ctx.accounts.state,
+        &ctx.accounts.reward_vault,
+        &ctx.accounts.submitter,
+        &ctx.accounts.system_program,
+        ctx.bumps.reward_vault,
+        accepted_headers,
+    )
+}
+
+#[derive(Accounts)]
+pub struct FinalizeHeaderBuffer<'info> {
+    #[account(
+        mut,
+        seeds = [b"btc_light_client"],
+        bump,
+        constraint = state.can_submit(&submitter.key(), submitter_allowlist.as_deref())
+            @ BtcLightClientError::UnauthorizedSigner,
+        constraint = !state.submission_paused @ BtcLightClientError::SubmissionPaused
+    )]
+    pub state: Account<'info, BtcLightClientState>,
+
+    #[account(seeds = [b"submitter_allowlist"], bump)]
+    pub submitter_allowlist: Option<Account<'info, SubmitterAllowlist>>,
+
+    #[account(
+        mut,
+        seeds = [b"header_buffer", submitter.key().as_ref()],
+        bump,
+        close = submitter
+    )]
+    pub header_buffer: AccountLoader<'info, HeaderBuffer>,
+
+    /// CHECK: entry of the block the buffered headers build on, checked against the buffer's
+    /// start height; unused once a header store is attached
+    pub parent_block_hash_entry: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        mut,
+        constraint = header_store.key() == state.header_store @ BtcLightClientError::InvalidHeaderStore
+    )]
+    pub header_store: Option<AccountLoader<'info, HeaderStore>>,
+
+    /// Lamport vault that pays relayers for accepted headers
+    #[account(mut, seeds = [b"relayer_reward_vault"], bump)]
+    pub reward_vault: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub submitter: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
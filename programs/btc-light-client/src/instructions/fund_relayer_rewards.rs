@@ -0,0 +1,35 @@
+use crate::events::RelayerRewardsFunded;
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+
+#[derive(Accounts)]
+pub struct FundRelayerRewards<'info> {
+    /// Lamport vault that pays relayers for accepted headers
+    #[account(mut, seeds = [b"relayer_reward_vault"], bump)]
+    pub reward_vault: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Anyone can top up the vault
+pub fn fund_relayer_rewards(ctx: Context<FundRelayerRewards>, amount: u64) -> Result<()> {
+    transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.funder.to_account_info(),
+                to: ctx.accounts.reward_vault.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    emit!(RelayerRewardsFunded {
+        funder: ctx.accounts.funder.key(),
+        amount,
+    });
+
+    Ok(())
+}
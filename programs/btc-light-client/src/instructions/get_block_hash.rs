@@ -0,0 +1,37 @@
+use crate::{
+    chain::canonical_block_hash,
+    errors::BtcLightClientError,
+    state::{BlockHashEntry, BtcLightClientState, HeaderStore},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(block_height: u64)]
+pub struct GetBlockHash<'info> {
+    #[account(seeds = [b"btc_light_client"], bump)]
+    pub state: Account<'info, BtcLightClientState>,
+
+    #[account(
+        seeds = [b"block_hash_entry", block_height.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub block_hash_entry: Option<Account<'info, BlockHashEntry>>,
+
+    #[account(
+        constraint = header_store.key() == state.header_store @ BtcLightClientError::InvalidHeaderStore
+    )]
+    pub header_store: Option<AccountLoader<'info, HeaderStore>>,
+}
+
+pub fn get_block_hash(ctx: Context<GetBlockHash>, block_height: u64) -> Result<[u8; 32]> {
+    let header_store = match &ctx.accounts.header_store {
+        Some(loader) => Some(loader.load()?),
+        None => None,
+    };
+    canonical_block_hash(
+        &ctx.accounts.state,
+        block_height,
+        ctx.accounts.block_hash_entry.as_deref(),
+        header_store.as_deref(),
+    )
+}
@@ -0,0 +1,12 @@
+use crate::state::BtcLightClientState;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct GetMedianTimePast<'info> {
+    #[account(seeds = [b"btc_light_client"], bump)]
+    pub state: Account<'info, BtcLightClientState>,
+}
+
+pub fn get_median_time_past(ctx: Context<GetMedianTimePast>) -> Result<u32> {
+    Ok(ctx.accounts.state.median_time_past())
+}
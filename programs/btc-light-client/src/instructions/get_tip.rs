@@ -0,0 +1,27 @@
+use crate::state::BtcLightClientState;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct GetTip<'info> {
+    #[account(seeds = [b"btc_light_client"], bump)]
+    pub state: Account<'info, BtcLightClientState>,
+}
+
+/// Latest block of the heaviest verified chain
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ChainTip {
+    pub block_height: u64,
+    pub block_hash: [u8; 32],
+    pub block_time: u32,
+    pub chainwork: [u8; 32],
+}
+
+pub fn get_tip(ctx: Context<GetTip>) -> Result<ChainTip> {
+    let state = &ctx.accounts.state;
+    Ok(ChainTip {
+        block_height: state.latest_block_height,
+        block_hash: state.latest_block_hash,
+        block_time: state.latest_block_time,
+        chainwork: state.latest_chainwork,
+    })
+}
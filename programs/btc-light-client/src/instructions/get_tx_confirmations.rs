@@ -0,0 +1,64 @@
+use crate::{
+    chain::canonical_block_hash,
+    errors::BtcLightClientError,
+    state::{BlockHashEntry, BtcLightClientState, HeaderStore, TxVerifiedState},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(tx_id: [u8; 32])]
+pub struct GetTxConfirmations<'info> {
+    #[account(
+        seeds = [b"btc_light_client"],
+        bump,
+        constraint = !state.verification_paused @ BtcLightClientError::VerificationPaused
+    )]
+    pub state: Account<'info, BtcLightClientState>,
+
+    #[account(seeds = [b"tx_verified_state".as_ref(), tx_id.as_ref()], bump)]
+    pub tx_verified_state: Account<'info, TxVerifiedState>,
+
+    #[account(
+        seeds = [b"block_hash_entry", tx_verified_state.block_height.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub block_hash_entry: Option<Account<'info, BlockHashEntry>>,
+
+    #[account(
+        constraint = header_store.key() == state.header_store @ BtcLightClientError::InvalidHeaderStore
+    )]
+    pub header_store: Option<AccountLoader<'info, HeaderStore>>,
+}
+
+// Confirmations of the block the transaction was verified in, counting that block. Zero once
+// the block has been reorganized out of the chain. Blocks that were final when the transaction
+// was proven need no entry.
+pub fn get_tx_confirmations(ctx: Context<GetTxConfirmations>, _tx_id: [u8; 32]) -> Result<u64> {
+    let state = &ctx.accounts.state;
+    let tx_verified_state = &ctx.accounts.tx_verified_state;
+    if !tx_verified_state.is_verified || tx_verified_state.block_height > state.latest_block_height
+    {
+        return Ok(0);
+    }
+    // A record proven in a final block stays canonical, even once the block's entry is
+    // overwritten in the header store or closed
+    if tx_verified_state.is_final {
+        return Ok(state.latest_block_height - tx_verified_state.block_height + 1);
+    }
+
+    let header_store = match &ctx.accounts.header_store {
+        Some(loader) => Some(loader.load()?),
+        None => None,
+    };
+    let block_hash = canonical_block_hash(
+        state,
+        tx_verified_state.block_height,
+        ctx.accounts.block_hash_entry.as_deref(),
+        header_store.as_deref(),
+    )?;
+    if block_hash != tx_verified_state.block_hash {
+        return Ok(0);
+    }
+
+    Ok(state.latest_block_height - tx_verified_state.block_height + 1)
+}
@@ -0,0 +1,67 @@
+use crate::{
+    chain::{branch_parent, load_entry},
+    errors::BtcLightClientError,
+    state::*,
+};
+use anchor_lang::prelude::*;
+
+// Each relayer gets one buffer, so concurrent uploads never interleave
+#[derive(Accounts)]
+#[instruction(start_height: u64)]
+pub struct InitHeaderBuffer<'info> {
+    #[account(seeds = [b"btc_light_client"], bump)]
+    pub state: Account<'info, BtcLightClientState>,
+
+    #[account(
+        init,
+        payer = submitter,
+        space = HeaderBuffer::space(0),
+        seeds = [b"header_buffer", submitter.key().as_ref()],
+        bump
+    )]
+    pub header_buffer: AccountLoader<'info, HeaderBuffer>,
+
+    /// Entry of the block the buffered headers build on, unused once a header store is attached
+    #[account(
+        seeds = [b"block_hash_entry", start_height.saturating_sub(1).to_le_bytes().as_ref()],
+        bump
+    )]
+    pub parent_block_hash_entry: Option<Account<'info, BlockHashEntry>>,
+
+    #[account(
+        constraint = header_store.key() == state.header_store @ BtcLightClientError::InvalidHeaderStore
+    )]
+    pub header_store: Option<AccountLoader<'info, HeaderStore>>,
+
+    #[account(mut)]
+    pub submitter: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Seeds the buffer with the parent block, which every appended chunk is validated against
+pub fn init_header_buffer(ctx: Context<InitHeaderBuffer>, start_height: u64) -> Result<()> {
+    let state = &ctx.accounts.state;
+    // Signet headers go through `submit_signet_header` along with their block solution
+    require!(
+        state.network != Network::Signet,
+        BtcLightClientError::SignetSolutionRequired
+    );
+
+    let parent_height = start_height
+        .checked_sub(1)
+        .ok_or(BtcLightClientError::ParentBlockNotYetSubmitted)?;
+    let parent = load_entry(
+        state,
+        parent_height,
+        ctx.accounts.parent_block_hash_entry.as_deref(),
+        ctx.accounts.header_store.as_ref(),
+    )?;
+    let tip = branch_parent(state, &parent)?;
+
+    let mut header_buffer = ctx.accounts.header_buffer.load_init()?;
+    header_buffer.start_height = start_height;
+    header_buffer.count = 0;
+    header_buffer.parent_hash = parent.hash;
+    header_buffer.tip = tip;
+    Ok(())
+}
@@ -0,0 +1,26 @@
+use crate::state::TxBuffer;
+use anchor_lang::prelude::*;
+
+// Buffers are keyed by uploader and txid, so several large transactions can be uploaded at once
+#[derive(Accounts)]
+#[instruction(tx_id: [u8; 32])]
+pub struct InitTxBuffer<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = TxBuffer::space(0),
+        seeds = [b"tx_buffer", payer.key().as_ref(), tx_id.as_ref()],
+        bump
+    )]
+    pub tx_buffer: AccountLoader<'info, TxBuffer>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn init_tx_buffer(ctx: Context<InitTxBuffer>, tx_id: [u8; 32]) -> Result<()> {
+    let mut tx_buffer = ctx.accounts.tx_buffer.load_init()?;
+    tx_buffer.tx_id = tx_id;
+    Ok(())
+}
@@ -1,28 +1,55 @@
 use crate::state::*;
 use anchor_lang::prelude::*;
+use bitcoin::pow::Target;
 
 pub fn initialize(
     ctx: Context<Initialize>,
     block_height: u64,
     block_hash: [u8; 32],
-    block_time: u32,
     expected_target: [u8; 32],
-    is_testnet: bool,
+    context: ChainContext,
+    network: Network,
     min_confirmations: u64,
 ) -> Result<()> {
     let state = &mut ctx.accounts.state;
+    let recent_block_times = context.recent_block_times();
 
     state.latest_block_height = block_height;
     state.latest_block_hash = block_hash;
-    state.latest_block_time = block_time;
+    state.latest_block_time = context.block_time;
     state.latest_period_target = expected_target;
-    state.is_testnet = is_testnet;
+    state.network = network;
     state.min_confirmations = min_confirmations;
     state.owner = ctx.accounts.payer.key();
+    state.latest_chainwork = context.chainwork;
+    state.period_start_time = context.period_start_time;
+    state.recent_block_times = recent_block_times;
+    state.permissionless_relaying = false;
+    state.relayer_reward = 0;
+    state.header_store = Pubkey::default();
+    state.block_entry_retention = 0;
+    state.tx_consumer = Pubkey::default();
+    state.require_coinbase_proof = true;
+    state.pending_owner = Pubkey::default();
+    state.max_reorg_depth = 0;
+    // The trusted starting block is final by construction
+    state.checkpoint_height = block_height;
+    state.checkpoint_hash = block_hash;
+    state.submission_paused = false;
+    state.verification_paused = false;
+    state.version = STATE_VERSION;
 
     let block_hash_entry = &mut ctx.accounts.block_hash_entry;
     block_hash_entry.height = block_height;
     block_hash_entry.hash = block_hash;
+    block_hash_entry.chainwork = context.chainwork;
+    block_hash_entry.recent_block_times = recent_block_times;
+    block_hash_entry.bits = Target::from_be_bytes(expected_target)
+        .to_compact_lossy()
+        .to_consensus();
+    block_hash_entry.period_target = expected_target;
+    block_hash_entry.period_start_time = context.period_start_time;
+    block_hash_entry.payer = ctx.accounts.payer.key();
 
     Ok(())
 }
@@ -42,7 +69,7 @@ pub struct Initialize<'info> {
     #[account(
         init,
         payer = payer,
-        space = 8 + 8 + 32,
+        space = BlockHashEntry::SPACE,
         seeds = [b"block_hash_entry".as_ref(), block_height.to_le_bytes().as_ref()],
         bump
     )]
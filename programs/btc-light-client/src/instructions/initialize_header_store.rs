@@ -0,0 +1,40 @@
+use crate::{errors::BtcLightClientError, state::*};
+use anchor_lang::prelude::*;
+
+pub fn initialize_header_store(ctx: Context<InitializeHeaderStore>) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    let mut header_store = ctx.accounts.header_store.load_init()?;
+
+    // Seed the ring with the current tip, new headers build on it from here
+    header_store.base_height = state.latest_block_height;
+    header_store.put(&ctx.accounts.tip_block_hash_entry);
+
+    state.header_store = ctx.accounts.header_store.key();
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeHeaderStore<'info> {
+    #[account(
+        mut,
+        seeds = [b"btc_light_client"],
+        bump,
+        constraint = state.owner == authority.key() @ BtcLightClientError::UnauthorizedSigner,
+        constraint = state.header_store == Pubkey::default() @ BtcLightClientError::HeaderStoreAlreadyInitialized
+    )]
+    pub state: Account<'info, BtcLightClientState>,
+
+    /// Pre-allocated with `HeaderStore::SPACE` bytes and owned by this program, as the account
+    /// is too large to be created through CPI
+    #[account(zero)]
+    pub header_store: AccountLoader<'info, HeaderStore>,
+
+    #[account(
+        seeds = [b"block_hash_entry", state.latest_block_height.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub tip_block_hash_entry: Account<'info, BlockHashEntry>,
+
+    pub authority: Signer<'info>,
+}
@@ -0,0 +1,89 @@
+use crate::errors::BtcLightClientError;
+use crate::state::{BlockHashEntry, BtcLightClientState, ChainContext, LegacyBlockHashEntry};
+use crate::utils::realloc_account;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(block_height: u64)]
+pub struct MigrateBlockHashEntry<'info> {
+    /// The BTC Light Client state account, already migrated
+    #[account(
+        seeds = [b"btc_light_client"],
+        bump,
+        // ensure the caller is the owner
+        constraint = state.owner == authority.key() @ BtcLightClientError::UnauthorizedSigner
+    )]
+    pub state: Account<'info, BtcLightClientState>,
+
+    /// CHECK: Entry of the block at `block_height` in the legacy layout, which `Account`
+    /// cannot deserialize yet
+    #[account(
+        mut,
+        seeds = [b"block_hash_entry", block_height.to_le_bytes().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub block_hash_entry: UncheckedAccount<'info>,
+
+    /// The authority that can migrate entries and pays the extra rent (must be owner)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Moves a block hash entry from the legacy layout to the current one, with the chain
+/// context, difficulty period target and bits the owner supplies for the block. Headers can
+/// only build on migrated entries, so the latest block's entry is migrated first; its context
+/// must match the migrated state.
+/// The authority pays the extra rent and gets the whole rent back when the entry is closed.
+pub fn migrate_block_hash_entry(
+    ctx: Context<MigrateBlockHashEntry>,
+    block_height: u64,
+    context: ChainContext,
+    period_target: [u8; 32],
+    bits: u32,
+) -> Result<()> {
+    let recent_block_times = context.recent_block_times();
+    let state = &ctx.accounts.state;
+    let entry_info = ctx.accounts.block_hash_entry.to_account_info();
+    require!(
+        entry_info.data_len() != BlockHashEntry::SPACE,
+        BtcLightClientError::StateUpToDate
+    );
+    let legacy = LegacyBlockHashEntry::try_from_account_data(&entry_info.data.borrow())?;
+    require!(
+        legacy.height == block_height && block_height <= state.latest_block_height,
+        BtcLightClientError::MigrationMismatch
+    );
+    if block_height == state.latest_block_height {
+        require!(
+            legacy.hash == state.latest_block_hash
+                && context.chainwork == state.latest_chainwork
+                && recent_block_times == state.recent_block_times
+                && period_target == state.latest_period_target
+                && context.period_start_time == state.period_start_time,
+            BtcLightClientError::MigrationMismatch
+        );
+    }
+
+    realloc_account(
+        &entry_info,
+        &ctx.accounts.authority.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        BlockHashEntry::SPACE,
+    )?;
+
+    BlockHashEntry {
+        height: block_height,
+        hash: legacy.hash,
+        chainwork: context.chainwork,
+        recent_block_times,
+        bits,
+        period_target,
+        period_start_time: context.period_start_time,
+        payer: ctx.accounts.authority.key(),
+    }
+    .try_serialize(&mut &mut entry_info.data.borrow_mut()[..])?;
+
+    Ok(())
+}
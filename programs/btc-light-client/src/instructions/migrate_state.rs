@@ -0,0 +1,101 @@
+use crate::errors::BtcLightClientError;
+use crate::events::StateMigrated;
+use crate::state::{
+    BtcLightClientState, ChainContext, LegacyBtcLightClientState, Network, STATE_VERSION,
+};
+use crate::utils::realloc_account;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct MigrateState<'info> {
+    /// CHECK: The BTC Light Client state account in an older layout, which `Account`
+    /// cannot deserialize yet; the owner is checked against the stored data
+    #[account(mut, seeds = [b"btc_light_client"], bump, owner = crate::ID)]
+    pub state: UncheckedAccount<'info>,
+
+    /// The authority that can migrate the state and pays the extra rent (must be owner)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Moves a state account from the layout its stored version names to the current one.
+pub fn migrate_state(
+    ctx: Context<MigrateState>,
+    context: ChainContext,
+    network: Network,
+) -> Result<()> {
+    let version = BtcLightClientState::layout_version(&ctx.accounts.state.data.borrow())?;
+    match version {
+        0 => migrate_legacy_state(&ctx, context, network)?,
+        STATE_VERSION => return err!(BtcLightClientError::StateUpToDate),
+        _ => return err!(BtcLightClientError::UnsupportedStateVersion),
+    }
+
+    // emit event
+    emit!(StateMigrated {
+        old_version: version,
+        new_version: STATE_VERSION,
+        authority: ctx.accounts.authority.key(),
+    });
+
+    Ok(())
+}
+
+/// The legacy state lacks the chain context header validation needs, so the owner supplies
+/// it for the latest block along with the network, which must agree with the legacy
+/// `is_testnet` flag. The latest block becomes the checkpoint, as the starting block does on
+/// `initialize`.
+fn migrate_legacy_state(
+    ctx: &Context<MigrateState>,
+    context: ChainContext,
+    network: Network,
+) -> Result<()> {
+    let state_info = ctx.accounts.state.to_account_info();
+    let legacy = LegacyBtcLightClientState::try_from_account_data(&state_info.data.borrow())?;
+    require!(
+        legacy.owner == ctx.accounts.authority.key(),
+        BtcLightClientError::UnauthorizedSigner
+    );
+    require!(
+        context.block_time == legacy.latest_block_time
+            && legacy.is_testnet == (network != Network::Mainnet),
+        BtcLightClientError::MigrationMismatch
+    );
+
+    realloc_account(
+        &state_info,
+        &ctx.accounts.authority.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        BtcLightClientState::SPACE,
+    )?;
+
+    let state = BtcLightClientState {
+        version: STATE_VERSION,
+        latest_block_hash: legacy.latest_block_hash,
+        latest_block_height: legacy.latest_block_height,
+        latest_block_time: legacy.latest_block_time,
+        latest_period_target: legacy.latest_period_target,
+        network,
+        min_confirmations: legacy.min_confirmations,
+        owner: legacy.owner,
+        latest_chainwork: context.chainwork,
+        period_start_time: context.period_start_time,
+        recent_block_times: context.recent_block_times(),
+        permissionless_relaying: false,
+        relayer_reward: 0,
+        header_store: Pubkey::default(),
+        block_entry_retention: 0,
+        tx_consumer: Pubkey::default(),
+        require_coinbase_proof: true,
+        pending_owner: Pubkey::default(),
+        max_reorg_depth: 0,
+        checkpoint_height: legacy.latest_block_height,
+        checkpoint_hash: legacy.latest_block_hash,
+        submission_paused: false,
+        verification_paused: false,
+    };
+    state.try_serialize(&mut &mut state_info.data.borrow_mut()[..])?;
+
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use crate::errors::BtcLightClientError;
+use crate::state::{LegacyTxVerifiedState, TxVerifiedState};
+use crate::utils::realloc_account;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(tx_id: [u8; 32])]
+pub struct MigrateTxVerifiedState<'info> {
+    /// CHECK: Verification record of `tx_id` in the legacy layout, which `Account` cannot
+    /// deserialize yet
+    #[account(
+        mut,
+        seeds = [b"tx_verified_state".as_ref(), tx_id.as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub tx_verified_state: UncheckedAccount<'info>,
+
+    /// Pays the extra rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Moves a verification record from the legacy layout to the current one. The legacy record
+/// lacks the block and outputs its transaction was proven with, so it becomes an unverified
+/// record the transaction is proven into again with any of the verify instructions.
+/// The payer pays the extra rent and gets the whole rent back when the record is closed.
+pub fn migrate_tx_verified_state(
+    ctx: Context<MigrateTxVerifiedState>,
+    _tx_id: [u8; 32],
+) -> Result<()> {
+    let record_info = ctx.accounts.tx_verified_state.to_account_info();
+    require!(
+        record_info.data_len() != TxVerifiedState::SPACE,
+        BtcLightClientError::StateUpToDate
+    );
+    LegacyTxVerifiedState::try_from_account_data(&record_info.data.borrow())?;
+
+    realloc_account(
+        &record_info,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        TxVerifiedState::SPACE,
+    )?;
+
+    TxVerifiedState {
+        is_verified: false,
+        payer: ctx.accounts.payer.key(),
+        wtxid: [0; 32],
+        block_height: 0,
+        block_hash: [0; 32],
+        outputs: Vec::new(),
+        recipient: None,
+        memo: Vec::new(),
+        is_final: false,
+    }
+    .try_serialize(&mut &mut record_info.data.borrow_mut()[..])?;
+
+    Ok(())
+}
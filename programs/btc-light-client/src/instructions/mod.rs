@@ -1,11 +1,83 @@
+pub mod accept_ownership;
+pub mod add_submitter;
+pub mod append_header_buffer;
+pub mod append_tx_buffer;
+pub mod close_block_hash_entry;
+pub mod close_header_buffer;
+pub mod close_tx_buffer;
+pub mod close_tx_verified_state;
 pub mod create_block_hash_account;
+pub mod finalize_header_buffer;
+pub mod fund_relayer_rewards;
+pub mod get_block_hash;
+pub mod get_median_time_past;
+pub mod get_tip;
+pub mod get_tx_confirmations;
+pub mod init_header_buffer;
+pub mod init_tx_buffer;
 pub mod initialize;
+pub mod initialize_header_store;
+pub mod migrate_block_hash_entry;
+pub mod migrate_state;
+pub mod migrate_tx_verified_state;
+pub mod propose_ownership;
+pub mod remove_submitter;
+pub mod set_checkpoint;
+pub mod set_signet_challenge;
 pub mod submit_headers;
+pub mod submit_signet_header;
+pub mod toggle_submission_pause;
+pub mod toggle_verification_pause;
+pub mod update_block_entry_retention;
+pub mod update_max_reorg_depth;
 pub mod update_min_confirmations;
+pub mod update_permissionless_relaying;
+pub mod update_relayer_reward;
+pub mod update_require_coinbase_proof;
+pub mod update_tx_consumer;
+pub mod verify_spend;
 pub mod verify_tx;
+pub mod verify_tx_from_buffer;
+pub mod verify_tx_witness;
 
+pub use accept_ownership::*;
+pub use add_submitter::*;
+pub use append_header_buffer::*;
+pub use append_tx_buffer::*;
+pub use close_block_hash_entry::*;
+pub use close_header_buffer::*;
+pub use close_tx_buffer::*;
+pub use close_tx_verified_state::*;
 pub use create_block_hash_account::*;
+pub use finalize_header_buffer::*;
+pub use fund_relayer_rewards::*;
+pub use get_block_hash::*;
+pub use get_median_time_past::*;
+pub use get_tip::*;
+pub use get_tx_confirmations::*;
+pub use init_header_buffer::*;
+pub use init_tx_buffer::*;
 pub use initialize::*;
+pub use initialize_header_store::*;
+pub use migrate_block_hash_entry::*;
+pub use migrate_state::*;
+pub use migrate_tx_verified_state::*;
+pub use propose_ownership::*;
+pub use remove_submitter::*;
+pub use set_checkpoint::*;
+pub use set_signet_challenge::*;
 pub use submit_headers::*;
+pub use submit_signet_header::*;
+pub use toggle_submission_pause::*;
+pub use toggle_verification_pause::*;
+pub use update_block_entry_retention::*;
+pub use update_max_reorg_depth::*;
 pub use update_min_confirmations::*;
+pub use update_permissionless_relaying::*;
+pub use update_relayer_reward::*;
+pub use update_require_coinbase_proof::*;
+pub use update_tx_consumer::*;
+pub use verify_spend::*;
 pub use verify_tx::*;
+pub use verify_tx_from_buffer::*;
+pub use verify_tx_witness::*;
@@ -0,0 +1,36 @@
+use crate::errors::BtcLightClientError;
+use crate::events::OwnershipTransferProposed;
+use crate::state::BtcLightClientState;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ProposeOwnership<'info> {
+    /// The BTC Light Client state account
+    #[account(
+        mut,
+        seeds = [b"btc_light_client"],
+        bump,
+        // ensure the caller is the owner
+        constraint = state.owner == authority.key() @ BtcLightClientError::UnauthorizedSigner
+    )]
+    pub state: Account<'info, BtcLightClientState>,
+
+    /// The authority that can hand over ownership (must be owner)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+// The transfer only takes effect once `new_owner` accepts it, proposing the default key
+// cancels a pending transfer
+pub fn propose_ownership(ctx: Context<ProposeOwnership>, new_owner: Pubkey) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    state.pending_owner = new_owner;
+
+    // emit event
+    emit!(OwnershipTransferProposed {
+        current_owner: state.owner,
+        pending_owner: new_owner,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use crate::errors::BtcLightClientError;
+use crate::events::SubmitterRemoved;
+use crate::state::{BtcLightClientState, SubmitterAllowlist};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct RemoveSubmitter<'info> {
+    /// The BTC Light Client state account
+    #[account(
+        seeds = [b"btc_light_client"],
+        bump,
+        // ensure the caller is the owner
+        constraint = state.owner == authority.key() @ BtcLightClientError::UnauthorizedSigner
+    )]
+    pub state: Account<'info, BtcLightClientState>,
+
+    #[account(mut, seeds = [b"submitter_allowlist"], bump)]
+    pub submitter_allowlist: Account<'info, SubmitterAllowlist>,
+
+    /// The authority that can remove submitters (must be owner)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+pub fn remove_submitter(ctx: Context<RemoveSubmitter>, submitter: Pubkey) -> Result<()> {
+    let submitters = &mut ctx.accounts.submitter_allowlist.submitters;
+    let position = submitters
+        .iter()
+        .position(|allowed| *allowed == submitter)
+        .ok_or(BtcLightClientError::SubmitterNotAllowed)?;
+    submitters.swap_remove(position);
+
+    // emit event
+    emit!(SubmitterRemoved {
+        submitter,
+        authority: ctx.accounts.authority.key(),
+    });
+
+    Ok(())
+}
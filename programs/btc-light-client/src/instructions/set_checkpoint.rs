@@ -0,0 +1,75 @@
+use crate::chain::canonical_block_hash;
+use crate::errors::BtcLightClientError;
+use crate::events::CheckpointSet;
+use crate::state::{BlockHashEntry, BtcLightClientState, HeaderStore};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(block_height: u64)]
+pub struct SetCheckpoint<'info> {
+    /// The BTC Light Client state account
+    #[account(
+        mut,
+        seeds = [b"btc_light_client"],
+        bump,
+        // ensure the caller is the owner
+        constraint = state.owner == authority.key() @ BtcLightClientError::UnauthorizedSigner
+    )]
+    pub state: Account<'info, BtcLightClientState>,
+
+    #[account(
+        seeds = [b"block_hash_entry", block_height.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub block_hash_entry: Option<Account<'info, BlockHashEntry>>,
+
+    #[account(
+        constraint = header_store.key() == state.header_store @ BtcLightClientError::InvalidHeaderStore
+    )]
+    pub header_store: Option<AccountLoader<'info, HeaderStore>>,
+
+    /// The authority that can pin checkpoints (must be owner)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+/// Pins the block `block_hash` at `block_height` as final. It must be on the current chain
+/// and checkpoints only move forward, so an earlier one is never unpinned.
+pub fn set_checkpoint(
+    ctx: Context<SetCheckpoint>,
+    block_height: u64,
+    block_hash: [u8; 32],
+) -> Result<()> {
+    require!(
+        block_height >= ctx.accounts.state.checkpoint_height,
+        BtcLightClientError::InvalidCheckpoint
+    );
+
+    let header_store = match &ctx.accounts.header_store {
+        Some(loader) => Some(loader.load()?),
+        None => None,
+    };
+    let canonical_hash = canonical_block_hash(
+        &ctx.accounts.state,
+        block_height,
+        ctx.accounts.block_hash_entry.as_deref(),
+        header_store.as_deref(),
+    )?;
+    require!(
+        canonical_hash == block_hash,
+        BtcLightClientError::InvalidCheckpoint
+    );
+
+    let state = &mut ctx.accounts.state;
+    state.checkpoint_height = block_height;
+    state.checkpoint_hash = block_hash;
+
+    // emit event
+    emit!(CheckpointSet {
+        block_height,
+        block_hash,
+        authority: ctx.accounts.authority.key(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use crate::{
+    errors::BtcLightClientError, events::SignetChallengeSet, signet::SignetChallenge, state::*,
+};
+use anchor_lang::prelude::*;
+use bitcoin::Script;
+
+#[derive(Accounts)]
+#[instruction(challenge: Vec<u8>)]
+pub struct SetSignetChallenge<'info> {
+    #[account(
+        seeds = [b"btc_light_client"],
+        bump,
+        constraint = state.owner == authority.key() @ BtcLightClientError::UnauthorizedSigner,
+        constraint = state.network == Network::Signet @ BtcLightClientError::NotSignet
+    )]
+    pub state: Account<'info, BtcLightClientState>,
+
+    // The challenge defines the signet, so it is set once
+    #[account(
+        init,
+        payer = authority,
+        space = SignetConfig::space(&challenge),
+        seeds = [b"signet_config"],
+        bump
+    )]
+    pub signet_config: Account<'info, SignetConfig>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn set_signet_challenge(ctx: Context<SetSignetChallenge>, challenge: Vec<u8>) -> Result<()> {
+    // Reject challenges the light client could never verify a solution for
+    SignetChallenge::parse(Script::from_bytes(&challenge))?;
+
+    ctx.accounts.signet_config.challenge = challenge.clone();
+
+    emit!(SignetChallengeSet {
+        challenge,
+        authority: ctx.accounts.authority.key(),
+    });
+
+    Ok(())
+}
@@ -1,141 +1,115 @@
 use crate::{
-    errors::BtcLightClientError,
-    events::{ChainReorg, NewTip},
-    state::*,
-    utils::{get_and_verify_block_hash_account, mul_in_place},
+    chain::store_headers, errors::BtcLightClientError, events::RelayerRewardPaid,
+    header::RawHeader, state::*,
 };
 use anchor_lang::prelude::*;
-use bitcoin::{block::Header as BlockHeader, consensus::deserialize, hashes::Hash};
+use anchor_lang::system_program::{transfer, Transfer};
 
 pub fn submit_block_headers(
     ctx: Context<SubmitBlockHeaders>,
     block_height: u64,
     headers: Vec<u8>,
 ) -> Result<()> {
-    let headers: Vec<BlockHeader> = headers
-        .chunks(80)
-        .map(|chunk| deserialize(chunk))
-        .collect::<std::result::Result<_, _>>()
-        .map_err(|_| BtcLightClientError::InvalidHeader)?;
-
-    let state = &mut ctx.accounts.state;
-
-    require!(!headers.is_empty(), BtcLightClientError::NoHeaders);
-
-    require!(
-        ctx.remaining_accounts.len() == headers.len(),
-        BtcLightClientError::InvalidAccountNumber
-    );
-
-    let old_period = state.latest_block_height / 2016;
-    let new_height = block_height + headers.len() as u64 - 1;
-    let new_period = new_height / 2016;
-
+    // Signet headers go through `submit_signet_header` along with their block solution
     require!(
-        new_period >= old_period,
-        BtcLightClientError::OldDifficultyPeriod
+        ctx.accounts.state.network != Network::Signet,
+        BtcLightClientError::SignetSolutionRequired
     );
 
-    let mut num_reorged = 0;
-
-    let mut prev_hash = state.latest_block_hash;
-
-    for (i, header) in headers.iter().enumerate() {
-        let current_height = block_height + i as u64;
-        let hash = header.block_hash();
-        let hash_bytes = hash.to_byte_array();
+    let headers = RawHeader::parse_all(&headers)?;
+
+    let accepted_headers = store_headers(
+        &mut ctx.accounts.state,
+        block_height,
+        &headers,
+        ctx.accounts.parent_block_hash_entry.as_deref(),
+        ctx.accounts.header_store.as_ref(),
+        ctx.remaining_accounts,
+        ctx.program_id,
+    )?;
+
+    pay_relayer_reward(
+        &ctx.accounts.state,
+        &ctx.accounts.reward_vault,
+        &ctx.accounts.submitter,
+        &ctx.accounts.system_program,
+        ctx.bumps.reward_vault,
+        accepted_headers,
+    )
+}
 
-        // Get or create block hash account
-        let block_hash_entry = get_and_verify_block_hash_account(
-            &ctx.remaining_accounts[i],
-            current_height,
-            ctx.program_id,
+/// Pays the relayer for every header that changed the chain, as far as the vault allows
+pub(crate) fn pay_relayer_reward<'info>(
+    state: &BtcLightClientState,
+    reward_vault: &SystemAccount<'info>,
+    submitter: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    reward_vault_bump: u8,
+    accepted_headers: u64,
+) -> Result<()> {
+    let available = reward_vault
+        .lamports()
+        .saturating_sub(Rent::get()?.minimum_balance(0));
+    let reward = state
+        .relayer_reward
+        .saturating_mul(accepted_headers)
+        .min(available);
+    if reward > 0 {
+        transfer(
+            CpiContext::new_with_signer(
+                system_program.to_account_info(),
+                Transfer {
+                    from: reward_vault.to_account_info(),
+                    to: submitter.to_account_info(),
+                },
+                &[&[b"relayer_reward_vault", &[reward_vault_bump]]],
+            ),
+            reward,
         )?;
 
-        if block_hash_entry.hash != [0; 32] && block_hash_entry.hash != hash_bytes {
-            num_reorged += 1;
-        }
-
-        // Update block hash
-        let mut block_hash_entry =
-            BlockHashEntry::try_deserialize(&mut &ctx.remaining_accounts[i].data.borrow_mut()[..])?;
-        block_hash_entry.hash = hash_bytes;
-        block_hash_entry.height = current_height;
-        block_hash_entry
-            .try_serialize(&mut &mut ctx.remaining_accounts[i].data.borrow_mut()[..])?;
-
-        // Verify previous block hash
-        require!(
-            prev_hash != [0; 32],
-            BtcLightClientError::ParentBlockNotYetSubmitted
-        );
-
-        if num_reorged == 0 {
-            require!(
-                header.prev_blockhash.to_byte_array() == prev_hash,
-                BtcLightClientError::InvalidPrevHash
-            );
-        }
-        prev_hash = hash_bytes;
-
-        // Verify PoW and difficulty
-        let target = header.target();
-        require!(
-            target.is_met_by(hash),
-            BtcLightClientError::InvalidProofOfWork
-        );
-
-        let new_target = target.to_be_bytes();
-        if current_height % 2016 == 0 {
-            if !state.is_testnet {
-                let mut prev_target = state.latest_period_target;
-                mul_in_place(&mut prev_target, 4);
-                require!(
-                    new_target < prev_target,
-                    BtcLightClientError::InvalidDifficultyAdjustment
-                );
-            }
-            state.latest_period_target = new_target;
-        } else if !state.is_testnet {
-            require!(
-                new_target == state.latest_period_target,
-                BtcLightClientError::InvalidDifficultyAdjustment
-            );
-        }
-    }
-    let last_header = headers.last().unwrap();
-    let new_tip = last_header.block_hash().to_byte_array();
-    if num_reorged > 0 {
-        emit!(ChainReorg {
-            reorg_count: num_reorged,
-            old_tip: state.latest_block_hash,
-            new_tip,
+        emit!(RelayerRewardPaid {
+            relayer: submitter.key(),
+            accepted_headers,
+            amount: reward,
         });
     }
 
-    state.latest_block_height = new_height;
-    state.latest_block_hash = new_tip;
-    state.latest_block_time = last_header.time;
-
-    emit!(NewTip {
-        block_height: new_height,
-        block_time: state.latest_block_time,
-        block_hash: new_tip,
-    });
-
     Ok(())
 }
 
 #[derive(Accounts)]
+#[instruction(block_height: u64)]
 pub struct SubmitBlockHeaders<'info> {
     #[account(
-        mut, 
-        seeds = [b"btc_light_client"], 
+        mut,
+        seeds = [b"btc_light_client"],
         bump,
-        constraint = state.owner == submitter.key() @ BtcLightClientError::UnauthorizedSigner
+        constraint = state.can_submit(&submitter.key(), submitter_allowlist.as_deref())
+            @ BtcLightClientError::UnauthorizedSigner,
+        constraint = !state.submission_paused @ BtcLightClientError::SubmissionPaused
     )]
     pub state: Account<'info, BtcLightClientState>,
 
+    #[account(seeds = [b"submitter_allowlist"], bump)]
+    pub submitter_allowlist: Option<Account<'info, SubmitterAllowlist>>,
+
+    /// Entry of the block the submitted headers build on, unused once a header store is attached
+    #[account(
+        seeds = [b"block_hash_entry", block_height.saturating_sub(1).to_le_bytes().as_ref()],
+        bump
+    )]
+    pub parent_block_hash_entry: Option<Account<'info, BlockHashEntry>>,
+
+    #[account(
+        mut,
+        constraint = header_store.key() == state.header_store @ BtcLightClientError::InvalidHeaderStore
+    )]
+    pub header_store: Option<AccountLoader<'info, HeaderStore>>,
+
+    /// Lamport vault that pays relayers for accepted headers
+    #[account(mut, seeds = [b"relayer_reward_vault"], bump)]
+    pub reward_vault: SystemAccount<'info>,
+
     #[account(mut)]
     pub submitter: Signer<'info>,
     pub system_program: Program<'info, System>,
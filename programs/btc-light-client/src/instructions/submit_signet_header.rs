@@ -0,0 +1,86 @@
+use crate::{
+    chain::store_headers, errors::BtcLightClientError, header::RawHeader,
+    instructions::submit_headers::pay_relayer_reward, signet::verify_signet_solution, state::*,
+};
+use anchor_lang::prelude::*;
+use bitcoin::Script;
+
+// Signet headers carry next to no work, so each one is only accepted together with a
+// block solution satisfying the configured challenge
+pub fn submit_signet_header(
+    ctx: Context<SubmitSignetHeader>,
+    block_height: u64,
+    header: Vec<u8>,
+    coinbase_tx: Vec<u8>,
+    coinbase_merkle_proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    let headers = RawHeader::parse_all(&header)?;
+    require!(headers.len() == 1, BtcLightClientError::InvalidHeader);
+
+    verify_signet_solution(
+        &headers[0],
+        &coinbase_tx,
+        &coinbase_merkle_proof,
+        Script::from_bytes(&ctx.accounts.signet_config.challenge),
+    )?;
+
+    let accepted_headers = store_headers(
+        &mut ctx.accounts.state,
+        block_height,
+        &headers,
+        ctx.accounts.parent_block_hash_entry.as_deref(),
+        ctx.accounts.header_store.as_ref(),
+        ctx.remaining_accounts,
+        ctx.program_id,
+    )?;
+
+    pay_relayer_reward(
+        &ctx.accounts.state,
+        &ctx.accounts.reward_vault,
+        &ctx.accounts.submitter,
+        &ctx.accounts.system_program,
+        ctx.bumps.reward_vault,
+        accepted_headers,
+    )
+}
+
+#[derive(Accounts)]
+#[instruction(block_height: u64)]
+pub struct SubmitSignetHeader<'info> {
+    #[account(
+        mut,
+        seeds = [b"btc_light_client"],
+        bump,
+        constraint = state.can_submit(&submitter.key(), submitter_allowlist.as_deref())
+            @ BtcLightClientError::UnauthorizedSigner,
+        constraint = !state.submission_paused @ BtcLightClientError::SubmissionPaused
+    )]
+    pub state: Account<'info, BtcLightClientState>,
+
+    #[account(seeds = [b"submitter_allowlist"], bump)]
+    pub submitter_allowlist: Option<Account<'info, SubmitterAllowlist>>,
+
+    #[account(seeds = [b"signet_config"], bump)]
+    pub signet_config: Account<'info, SignetConfig>,
+
+    /// Entry of the block the submitted header builds on, unused once a header store is attached
+    #[account(
+        seeds = [b"block_hash_entry", block_height.saturating_sub(1).to_le_bytes().as_ref()],
+        bump
+    )]
+    pub parent_block_hash_entry: Option<Account<'info, BlockHashEntry>>,
+
+    #[account(
+        mut,
+        constraint = header_store.key() == state.header_store @ BtcLightClientError::InvalidHeaderStore
+    )]
+    pub header_store: Option<AccountLoader<'info, HeaderStore>>,
+
+    /// Lamport vault that pays relayers for accepted headers
+    #[account(mut, seeds = [b"relayer_reward_vault"], bump)]
+    pub reward_vault: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub submitter: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,55 @@
+use crate::errors::BtcLightClientError;
+use crate::events::SubmissionPauseUpdated;
+use crate::state::BtcLightClientState;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ToggleSubmissionPause<'info> {
+    /// The BTC Light Client state account
+    #[account(
+        mut,
+        seeds = [b"btc_light_client"],
+        bump,
+        // ensure the caller is the owner
+        constraint = state.owner == authority.key() @ BtcLightClientError::UnauthorizedSigner
+    )]
+    pub state: Account<'info, BtcLightClientState>,
+
+    /// The authority that can pause header submission (must be owner)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+pub fn pause_submission(ctx: Context<ToggleSubmissionPause>) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    require!(
+        !state.submission_paused,
+        BtcLightClientError::SubmissionAlreadyPaused
+    );
+    state.submission_paused = true;
+
+    // emit event
+    emit!(SubmissionPauseUpdated {
+        paused: true,
+        authority: ctx.accounts.authority.key(),
+    });
+
+    Ok(())
+}
+
+pub fn unpause_submission(ctx: Context<ToggleSubmissionPause>) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    require!(
+        state.submission_paused,
+        BtcLightClientError::SubmissionNotPaused
+    );
+    state.submission_paused = false;
+
+    // emit event
+    emit!(SubmissionPauseUpdated {
+        paused: false,
+        authority: ctx.accounts.authority.key(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,55 @@
+use crate::errors::BtcLightClientError;
+use crate::events::VerificationPauseUpdated;
+use crate::state::BtcLightClientState;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ToggleVerificationPause<'info> {
+    /// The BTC Light Client state account
+    #[account(
+        mut,
+        seeds = [b"btc_light_client"],
+        bump,
+        // ensure the caller is the owner
+        constraint = state.owner == authority.key() @ BtcLightClientError::UnauthorizedSigner
+    )]
+    pub state: Account<'info, BtcLightClientState>,
+
+    /// The authority that can pause transaction verification (must be owner)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+pub fn pause_verification(ctx: Context<ToggleVerificationPause>) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    require!(
+        !state.verification_paused,
+        BtcLightClientError::VerificationAlreadyPaused
+    );
+    state.verification_paused = true;
+
+    // emit event
+    emit!(VerificationPauseUpdated {
+        paused: true,
+        authority: ctx.accounts.authority.key(),
+    });
+
+    Ok(())
+}
+
+pub fn unpause_verification(ctx: Context<ToggleVerificationPause>) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    require!(
+        state.verification_paused,
+        BtcLightClientError::VerificationNotPaused
+    );
+    state.verification_paused = false;
+
+    // emit event
+    emit!(VerificationPauseUpdated {
+        paused: false,
+        authority: ctx.accounts.authority.key(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use crate::errors::BtcLightClientError;
+use crate::events::BlockEntryRetentionUpdated;
+use crate::state::BtcLightClientState;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UpdateBlockEntryRetention<'info> {
+    /// The BTC Light Client state account
+    #[account(
+        mut,
+        seeds = [b"btc_light_client"],
+        bump,
+        // ensure the caller is the owner
+        constraint = state.owner == authority.key() @ BtcLightClientError::UnauthorizedSigner
+    )]
+    pub state: Account<'info, BtcLightClientState>,
+
+    /// The authority that can update the retention depth (must be owner)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+/// Entries deeper than `block_entry_retention` can no longer serve as fork points or be used
+/// to verify transactions, so the depth should stay well above the expected reorg depth
+pub fn update_block_entry_retention(
+    ctx: Context<UpdateBlockEntryRetention>,
+    block_entry_retention: u64,
+) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    let old_value = state.block_entry_retention;
+    state.block_entry_retention = block_entry_retention;
+
+    // emit event
+    emit!(BlockEntryRetentionUpdated {
+        old_value,
+        new_value: block_entry_retention,
+        authority: ctx.accounts.authority.key(),
+    });
+
+    Ok(())
+}
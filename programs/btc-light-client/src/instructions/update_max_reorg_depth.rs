@@ -0,0 +1,39 @@
+use crate::errors::BtcLightClientError;
+use crate::events::MaxReorgDepthUpdated;
+use crate::state::BtcLightClientState;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UpdateMaxReorgDepth<'info> {
+    /// The BTC Light Client state account
+    #[account(
+        mut,
+        seeds = [b"btc_light_client"],
+        bump,
+        // ensure the caller is the owner
+        constraint = state.owner == authority.key() @ BtcLightClientError::UnauthorizedSigner
+    )]
+    pub state: Account<'info, BtcLightClientState>,
+
+    /// The authority that can update the maximum reorg depth (must be owner)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+pub fn update_max_reorg_depth(
+    ctx: Context<UpdateMaxReorgDepth>,
+    max_reorg_depth: u64,
+) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    let old_value = state.max_reorg_depth;
+    state.max_reorg_depth = max_reorg_depth;
+
+    // emit event
+    emit!(MaxReorgDepthUpdated {
+        old_value,
+        new_value: max_reorg_depth,
+        authority: ctx.accounts.authority.key(),
+    });
+
+    Ok(())
+}
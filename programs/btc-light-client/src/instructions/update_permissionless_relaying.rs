@@ -0,0 +1,37 @@
+use crate::errors::BtcLightClientError;
+use crate::events::PermissionlessRelayingUpdated;
+use crate::state::BtcLightClientState;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UpdatePermissionlessRelaying<'info> {
+    /// The BTC Light Client state account
+    #[account(
+        mut,
+        seeds = [b"btc_light_client"],
+        bump,
+        // ensure the caller is the owner
+        constraint = state.owner == authority.key() @ BtcLightClientError::UnauthorizedSigner
+    )]
+    pub state: Account<'info, BtcLightClientState>,
+
+    /// The authority that can open header submission to everyone (must be owner)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+pub fn update_permissionless_relaying(
+    ctx: Context<UpdatePermissionlessRelaying>,
+    enabled: bool,
+) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    state.permissionless_relaying = enabled;
+
+    // emit event
+    emit!(PermissionlessRelayingUpdated {
+        enabled,
+        authority: ctx.accounts.authority.key(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use crate::errors::BtcLightClientError;
+use crate::events::RelayerRewardUpdated;
+use crate::state::BtcLightClientState;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UpdateRelayerReward<'info> {
+    /// The BTC Light Client state account
+    #[account(
+        mut,
+        seeds = [b"btc_light_client"],
+        bump,
+        // ensure the caller is the owner
+        constraint = state.owner == authority.key() @ BtcLightClientError::UnauthorizedSigner
+    )]
+    pub state: Account<'info, BtcLightClientState>,
+
+    /// The authority that can update the relayer reward (must be owner)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+pub fn update_relayer_reward(ctx: Context<UpdateRelayerReward>, relayer_reward: u64) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    let old_value = state.relayer_reward;
+    state.relayer_reward = relayer_reward;
+
+    // emit event
+    emit!(RelayerRewardUpdated {
+        old_value,
+        new_value: relayer_reward,
+        authority: ctx.accounts.authority.key(),
+    });
+
+    Ok(())
+}
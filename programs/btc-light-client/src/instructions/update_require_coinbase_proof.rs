@@ -0,0 +1,37 @@
+use crate::errors::BtcLightClientError;
+use crate::events::RequireCoinbaseProofUpdated;
+use crate::state::BtcLightClientState;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UpdateRequireCoinbaseProof<'info> {
+    /// The BTC Light Client state account
+    #[account(
+        mut,
+        seeds = [b"btc_light_client"],
+        bump,
+        // ensure the caller is the owner
+        constraint = state.owner == authority.key() @ BtcLightClientError::UnauthorizedSigner
+    )]
+    pub state: Account<'info, BtcLightClientState>,
+
+    /// The authority that can require coinbase proofs (must be owner)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+pub fn update_require_coinbase_proof(
+    ctx: Context<UpdateRequireCoinbaseProof>,
+    enabled: bool,
+) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    state.require_coinbase_proof = enabled;
+
+    // emit event
+    emit!(RequireCoinbaseProofUpdated {
+        enabled,
+        authority: ctx.accounts.authority.key(),
+    });
+
+    Ok(())
+}
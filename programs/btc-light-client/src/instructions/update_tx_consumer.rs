@@ -0,0 +1,36 @@
+use crate::errors::BtcLightClientError;
+use crate::events::TxConsumerUpdated;
+use crate::state::BtcLightClientState;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UpdateTxConsumer<'info> {
+    /// The BTC Light Client state account
+    #[account(
+        mut,
+        seeds = [b"btc_light_client"],
+        bump,
+        // ensure the caller is the owner
+        constraint = state.owner == authority.key() @ BtcLightClientError::UnauthorizedSigner
+    )]
+    pub state: Account<'info, BtcLightClientState>,
+
+    /// The authority that can update the tx consumer (must be owner)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+pub fn update_tx_consumer(ctx: Context<UpdateTxConsumer>, tx_consumer: Pubkey) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    let old_value = state.tx_consumer;
+    state.tx_consumer = tx_consumer;
+
+    // emit event
+    emit!(TxConsumerUpdated {
+        old_value,
+        new_value: tx_consumer,
+        authority: ctx.accounts.authority.key(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,127 @@
+use super::verify_tx::{verify_block_header, verify_tx_inclusion, CoinbaseProof};
+use crate::{
+    errors::BtcLightClientError,
+    events::SpendVerified,
+    state::{BlockHashEntry, BtcLightClientState, HeaderStore, VerifiedSpend},
+};
+use anchor_lang::prelude::*;
+
+pub fn verify_spend(
+    ctx: Context<VerifySpend>,
+    block_height: u64,
+    spend_proof: BtcSpendProof,
+) -> Result<()> {
+    let header = verify_block_header(
+        &ctx.accounts.state,
+        block_height,
+        &spend_proof.block_header,
+        ctx.accounts.block_hash_entry.as_deref(),
+        ctx.accounts.header_store.as_ref(),
+    )?;
+
+    let tx = verify_tx_inclusion(
+        &ctx.accounts.state,
+        &header,
+        &spend_proof.raw_tx,
+        spend_proof.tx_id,
+        spend_proof.tx_index,
+        &spend_proof.merkle_proof,
+        spend_proof.coinbase_proof.as_ref(),
+    )?;
+
+    let input = tx
+        .inputs()
+        .get(spend_proof.input_index as usize)
+        .ok_or(BtcLightClientError::InvalidInputIndex)?;
+    require!(
+        *input.prev_txid == spend_proof.spent_tx_id && input.prev_vout == spend_proof.spent_vout,
+        BtcLightClientError::OutpointMismatch
+    );
+
+    let sequence = input.sequence;
+    let lock_time = tx.lock_time();
+    if let Some(expected_sequence) = spend_proof.expected_sequence {
+        require!(
+            sequence == expected_sequence,
+            BtcLightClientError::SequenceMismatch
+        );
+    }
+    if let Some(expected_lock_time) = spend_proof.expected_lock_time {
+        require!(
+            lock_time == expected_lock_time,
+            BtcLightClientError::LockTimeMismatch
+        );
+    }
+
+    emit!(SpendVerified {
+        block_height,
+        spent_tx_id: spend_proof.spent_tx_id,
+        spent_vout: spend_proof.spent_vout,
+        spending_tx_id: spend_proof.tx_id,
+    });
+
+    let verified_spend = &mut ctx.accounts.verified_spend;
+    verified_spend.spending_tx_id = spend_proof.tx_id;
+    verified_spend.input_index = spend_proof.input_index;
+    verified_spend.block_height = block_height;
+    verified_spend.block_hash = header.block_hash();
+    verified_spend.sequence = sequence;
+    verified_spend.lock_time = lock_time;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(block_height: u64, spend_proof: BtcSpendProof)]
+pub struct VerifySpend<'info> {
+    #[account(
+        seeds = [b"btc_light_client"],
+        bump,
+        constraint = !state.verification_paused @ BtcLightClientError::VerificationPaused
+    )]
+    pub state: Account<'info, BtcLightClientState>,
+
+    #[account(
+        init_if_needed,
+        seeds = [
+            b"verified_spend".as_ref(),
+            spend_proof.spent_tx_id.as_ref(),
+            spend_proof.spent_vout.to_le_bytes().as_ref()
+        ],
+        bump,
+        payer = payer,
+        space = VerifiedSpend::SPACE
+    )]
+    pub verified_spend: Account<'info, VerifiedSpend>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds = [b"block_hash_entry", block_height.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub block_hash_entry: Option<Account<'info, BlockHashEntry>>,
+
+    #[account(
+        constraint = header_store.key() == state.header_store @ BtcLightClientError::InvalidHeaderStore
+    )]
+    pub header_store: Option<AccountLoader<'info, HeaderStore>>,
+}
+
+/// Proof that a transaction in a verified block spends the outpoint `spent_tx_id:spent_vout`
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct BtcSpendProof {
+    pub block_header: Vec<u8>,
+    pub tx_id: [u8; 32],
+    pub tx_index: u32,
+    pub merkle_proof: Vec<[u8; 32]>,
+    pub raw_tx: Vec<u8>,
+    pub coinbase_proof: Option<CoinbaseProof>,
+    pub input_index: u32,
+    pub spent_tx_id: [u8; 32],
+    pub spent_vout: u32,
+    pub expected_sequence: Option<u32>,
+    pub expected_lock_time: Option<u32>,
+}
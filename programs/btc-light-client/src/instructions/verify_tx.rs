@@ -1,81 +1,338 @@
 use crate::{
+    chain::{canonical_block_hash, is_final},
     errors::BtcLightClientError,
     events::TransactionVerified,
-    state::{BlockHashEntry, BtcLightClientState, TxVerifiedState},
-    utils::{verify_merkle_proof, verify_output_script},
+    header::RawHeader,
+    state::{
+        BlockHashEntry, BtcLightClientState, HeaderStore, OutputScript, TxVerifiedState,
+        VerifiedOutput, MAX_VERIFIED_OUTPUTS,
+    },
+    transaction::{RawTransaction, RawTxOuts},
+    utils::{
+        parse_recipient_commitment, txid, verify_merkle_proof, verify_output_script,
+        AMBIGUOUS_TX_SIZE,
+    },
 };
 use anchor_lang::prelude::*;
-use bitcoin::{block::Header as BlockHeader, consensus::encode::deserialize, hashes::Hash};
+use bitcoin::{
+    consensus::encode::deserialize,
+    hashes::{sha256, Hash},
+};
 
 pub fn verify_transaction(
     ctx: Context<VerifyTransaction>,
     block_height: u64,
     tx_proof: BtcTxProof,
 ) -> Result<()> {
-    let state = &ctx.accounts.state;
-    let block_hash_entry = &ctx.accounts.block_hash_entry;
+    let header = verify_block_header(
+        &ctx.accounts.state,
+        block_height,
+        &tx_proof.block_header,
+        ctx.accounts.block_hash_entry.as_deref(),
+        ctx.accounts.header_store.as_ref(),
+    )?;
+
+    let tx = verify_tx_inclusion(
+        &ctx.accounts.state,
+        &header,
+        &tx_proof.raw_tx,
+        tx_proof.tx_id,
+        tx_proof.tx_index,
+        &tx_proof.merkle_proof,
+        tx_proof.coinbase_proof.as_ref(),
+    )?;
+
+    let outputs = verify_outputs(tx.outputs(), &tx_proof.expected_outputs)?;
+    let (recipient, memo) = verify_recipient(tx.outputs(), tx_proof.recipient_output)?;
+
+    emit!(TransactionVerified {
+        block_height,
+        tx_id: tx_proof.tx_id,
+        amount: outputs.iter().map(|output| output.value).sum(),
+    });
+
+    record_verification(
+        &ctx.accounts.state,
+        &mut ctx.accounts.tx_verified_state,
+        ctx.accounts.payer.key(),
+        block_height,
+        &header,
+        ProvenTx {
+            outputs,
+            recipient,
+            memo,
+            wtxid: [0; 32],
+        },
+    )?;
 
+    Ok(())
+}
+
+/// Parses `block_header` and checks it is the stored block at `block_height` with enough
+/// confirmations
+pub(crate) fn verify_block_header<'a>(
+    state: &BtcLightClientState,
+    block_height: u64,
+    block_header: &'a [u8],
+    block_hash_entry: Option<&BlockHashEntry>,
+    header_store: Option<&AccountLoader<HeaderStore>>,
+) -> Result<RawHeader<'a>> {
     require!(
         state.latest_block_height >= block_height + state.min_confirmations,
         BtcLightClientError::InsufficientConfirmations
     );
 
-    let header: BlockHeader = deserialize(&tx_proof.block_header)
-        .map_err(|_| BtcLightClientError::InvalidHeaderFormat)?;
+    let header = RawHeader::parse(block_header)?;
+
+    let header_store = match header_store {
+        Some(loader) => Some(loader.load()?),
+        None => None,
+    };
+    let block_hash = canonical_block_hash(
+        state,
+        block_height,
+        block_hash_entry,
+        header_store.as_deref(),
+    )?;
 
-    // Cache block hash calculation to avoid repeated computation
-    let header_hash_bytes = header.block_hash().to_byte_array();
     require!(
-        header_hash_bytes == block_hash_entry.hash,
+        header.block_hash() == block_hash,
         BtcLightClientError::BlockHashMismatch
     );
 
-    let tx: bitcoin::Transaction =
-        deserialize(&tx_proof.raw_tx).map_err(|_| BtcLightClientError::InvalidTransactionFormat)?;
+    Ok(header)
+}
+
+/// Parses `raw_tx` and proves it is the transaction `tx_id` at `tx_index` of the block of
+/// `header`
+pub(crate) fn verify_tx_inclusion<'a>(
+    state: &BtcLightClientState,
+    header: &RawHeader,
+    raw_tx: &'a [u8],
+    tx_id: [u8; 32],
+    tx_index: u32,
+    merkle_proof: &[[u8; 32]],
+    coinbase_proof: Option<&CoinbaseProof>,
+) -> Result<RawTransaction<'a>> {
+    let tx = RawTransaction::parse(raw_tx)?;
 
     // Cache transaction ID calculation to avoid repeated computation
-    let tx_id_bytes = tx.txid().to_byte_array();
+    let tx_id_bytes = tx.txid();
     require!(
-        tx_id_bytes == tx_proof.tx_id,
+        tx_id_bytes == tx_id,
         BtcLightClientError::TransactionIdMismatch
     );
 
-    let output = tx
-        .output
-        .get(tx_proof.output_index as usize)
-        .ok_or(BtcLightClientError::InvalidOutputIndex)?;
+    verify_tx_size(tx.base_size())?;
+    verify_leaf_depth(state, header, coinbase_proof, merkle_proof.len())?;
 
     require!(
-        output.value >= bitcoin::Amount::from_sat(tx_proof.expected_amount),
-        BtcLightClientError::InsufficientAmount
+        verify_merkle_proof(tx_id_bytes, header.merkle_root(), tx_index, merkle_proof),
+        BtcLightClientError::InvalidMerkleProof
     );
 
+    Ok(tx)
+}
+
+/// Checks that a transaction of `tx_size` bytes without witness cannot pass for an inner node
+pub(crate) fn verify_tx_size(tx_size: usize) -> Result<()> {
+    // 64 bytes is the size of an inner merkle node, so such a leaf could be either
     require!(
-        verify_output_script(&output.script_pubkey, &tx_proof.expected_script_hash),
-        BtcLightClientError::InvalidOutputScript
+        tx_size != AMBIGUOUS_TX_SIZE,
+        BtcLightClientError::AmbiguousTransactionSize
     );
 
-    // Reuse the cached transaction ID to create Txid for merkle proof verification
-    let tx_hash = bitcoin::Txid::from_byte_array(tx_id_bytes);
+    Ok(())
+}
+
+/// A coinbase at the same depth shows a proof of `depth` levels ends at a real leaf, not an
+/// inner node. The depth is left unchecked only where the owner waived coinbase proofs.
+pub(crate) fn verify_leaf_depth(
+    state: &BtcLightClientState,
+    header: &RawHeader,
+    coinbase_proof: Option<&CoinbaseProof>,
+    depth: usize,
+) -> Result<()> {
+    match coinbase_proof {
+        Some(coinbase_proof) => {
+            verify_coinbase_proof(header, coinbase_proof, depth)?;
+        }
+        None => require!(
+            !state.require_coinbase_proof,
+            BtcLightClientError::CoinbaseProofRequired
+        ),
+    }
+
+    Ok(())
+}
+
+/// Checks the coinbase of the block of `header` and that its merkle proof has `depth` levels
+pub(crate) fn verify_coinbase_proof(
+    header: &RawHeader,
+    coinbase_proof: &CoinbaseProof,
+    depth: usize,
+) -> Result<bitcoin::Transaction> {
+    let coinbase: bitcoin::Transaction = deserialize(&coinbase_proof.coinbase_tx)
+        .map_err(|_| BtcLightClientError::InvalidTransactionFormat)?;
+    require!(
+        coinbase.is_coinbase(),
+        BtcLightClientError::InvalidCoinbaseProof
+    );
+    require!(
+        coinbase_proof.merkle_proof.len() == depth,
+        BtcLightClientError::InvalidMerkleProofDepth
+    );
     require!(
         verify_merkle_proof(
-            tx_hash,
-            header.merkle_root,
-            tx_proof.tx_index,
-            &tx_proof.merkle_proof
+            txid(&coinbase),
+            header.merkle_root(),
+            0,
+            &coinbase_proof.merkle_proof
         ),
-        BtcLightClientError::InvalidMerkleProof
+        BtcLightClientError::InvalidCoinbaseProof
     );
 
-    emit!(TransactionVerified {
-        block_height,
-        tx_id: tx_proof.tx_id,
-        amount: output.value.to_sat(),
-    });
+    Ok(coinbase)
+}
+
+/// Checks every expected output among `tx_outputs` and returns what was proven about them
+pub(crate) fn verify_outputs(
+    tx_outputs: RawTxOuts,
+    expected_outputs: &[ExpectedOutput],
+) -> Result<Vec<VerifiedOutput>> {
+    require!(
+        !expected_outputs.is_empty() && expected_outputs.len() <= MAX_VERIFIED_OUTPUTS,
+        BtcLightClientError::InvalidExpectedOutputs
+    );
+    // Ascending indices keep each output recorded once
+    require!(
+        expected_outputs
+            .windows(2)
+            .all(|pair| pair[0].index < pair[1].index),
+        BtcLightClientError::InvalidExpectedOutputs
+    );
+
+    let mut proven_scripts = Vec::with_capacity(expected_outputs.len());
+    let outputs = expected_outputs
+        .iter()
+        .map(|expected| {
+            let output = tx_outputs
+                .get(expected.index as usize)
+                .ok_or(BtcLightClientError::InvalidOutputIndex)?;
+
+            require!(
+                output.value >= expected.min_amount,
+                BtcLightClientError::InsufficientAmount
+            );
+
+            require!(
+                verify_output_script(output.script_pubkey, &expected.script),
+                BtcLightClientError::InvalidOutputScript
+            );
 
-    // set the tx state
-    let tx_verified_state = &mut ctx.accounts.tx_verified_state;
-    tx_verified_state.is_verified = true;
+            proven_scripts.push(output.script_pubkey);
+            Ok(VerifiedOutput {
+                index: expected.index,
+                value: output.value,
+                script_hash: sha256::Hash::hash(output.script_pubkey.as_bytes()).to_byte_array(),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    // Every output paying a proven script is proven too, so the recorded total paid to any
+    // script is the whole amount the transaction pays it
+    for (index, output) in tx_outputs.iter().enumerate() {
+        let proven = expected_outputs
+            .iter()
+            .any(|expected| expected.index as usize == index);
+        let pays_proven_script = proven_scripts.contains(&output.script_pubkey);
+        require!(
+            proven || !pays_proven_script,
+            BtcLightClientError::IncompleteScriptOutputs
+        );
+    }
+
+    Ok(outputs)
+}
+
+/// Reads the recipient commitment from output `recipient_output` of `tx_outputs`, if one is
+/// given. It must be the only OP_RETURN output, so no prover can pick another recipient.
+pub(crate) fn verify_recipient(
+    tx_outputs: RawTxOuts,
+    recipient_output: Option<u32>,
+) -> Result<(Option<Pubkey>, Vec<u8>)> {
+    let Some(index) = recipient_output else {
+        return Ok((None, Vec::new()));
+    };
+    let output = tx_outputs
+        .get(index as usize)
+        .ok_or(BtcLightClientError::InvalidOutputIndex)?;
+    let (recipient, memo) = parse_recipient_commitment(output.script_pubkey)
+        .ok_or(BtcLightClientError::InvalidRecipientCommitment)?;
+    require!(
+        tx_outputs
+            .iter()
+            .filter(|output| output.script_pubkey.is_op_return())
+            .count()
+            == 1,
+        BtcLightClientError::InvalidRecipientCommitment
+    );
+
+    Ok((Some(recipient), memo))
+}
+
+/// What a proof established about a transaction, beyond the block holding it
+pub(crate) struct ProvenTx {
+    pub outputs: Vec<VerifiedOutput>,
+    pub recipient: Option<Pubkey>,
+    pub memo: Vec<u8>,
+    pub wtxid: [u8; 32],
+}
+
+/// Records a proven transaction. A later proof never changes what is recorded, it only adds to
+/// it: outputs at new indices, a recipient or wtxid not proven yet, and the block holding the
+/// transaction after a reorg. Whether that block is final is re-evaluated on every proof.
+pub(crate) fn record_verification(
+    state: &BtcLightClientState,
+    tx_verified_state: &mut TxVerifiedState,
+    payer: Pubkey,
+    block_height: u64,
+    header: &RawHeader,
+    proven: ProvenTx,
+) -> Result<()> {
+    tx_verified_state.block_height = block_height;
+    tx_verified_state.block_hash = header.block_hash();
+    tx_verified_state.is_final = is_final(state, block_height);
+
+    if !tx_verified_state.is_verified {
+        tx_verified_state.is_verified = true;
+        // a migrated record keeps the payer of its extra rent
+        if tx_verified_state.payer == Pubkey::default() {
+            tx_verified_state.payer = payer;
+        }
+        tx_verified_state.outputs = proven.outputs;
+    } else {
+        for output in proven.outputs {
+            if let Err(position) = tx_verified_state
+                .outputs
+                .binary_search_by_key(&output.index, |recorded| recorded.index)
+            {
+                tx_verified_state.outputs.insert(position, output);
+            }
+        }
+        require!(
+            tx_verified_state.outputs.len() <= MAX_VERIFIED_OUTPUTS,
+            BtcLightClientError::InvalidExpectedOutputs
+        );
+    }
+
+    if tx_verified_state.recipient.is_none() && proven.recipient.is_some() {
+        tx_verified_state.recipient = proven.recipient;
+        tx_verified_state.memo = proven.memo;
+    }
+    if tx_verified_state.wtxid == [0; 32] {
+        tx_verified_state.wtxid = proven.wtxid;
+    }
 
     Ok(())
 }
@@ -83,7 +340,11 @@ pub fn verify_transaction(
 #[derive(Accounts)]
 #[instruction(block_height: u64, tx_proof: BtcTxProof)]
 pub struct VerifyTransaction<'info> {
-    #[account(seeds = [b"btc_light_client"], bump)]
+    #[account(
+        seeds = [b"btc_light_client"],
+        bump,
+        constraint = !state.verification_paused @ BtcLightClientError::VerificationPaused
+    )]
     pub state: Account<'info, BtcLightClientState>,
 
     #[account(
@@ -103,7 +364,12 @@ pub struct VerifyTransaction<'info> {
         seeds = [b"block_hash_entry", block_height.to_le_bytes().as_ref()],
         bump
     )]
-    pub block_hash_entry: Account<'info, BlockHashEntry>,
+    pub block_hash_entry: Option<Account<'info, BlockHashEntry>>,
+
+    #[account(
+        constraint = header_store.key() == state.header_store @ BtcLightClientError::InvalidHeaderStore
+    )]
+    pub header_store: Option<AccountLoader<'info, HeaderStore>>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
@@ -113,7 +379,24 @@ pub struct BtcTxProof {
     pub tx_index: u32,
     pub merkle_proof: Vec<[u8; 32]>,
     pub raw_tx: Vec<u8>,
-    pub output_index: u32,
-    pub expected_amount: u64,
-    pub expected_script_hash: [u8; 32],
+    /// Outputs to check, by ascending index
+    pub expected_outputs: Vec<ExpectedOutput>,
+    /// OP_RETURN output committing to the Solana recipient of a pegin
+    pub recipient_output: Option<u32>,
+    /// Coinbase proven at index 0 with the same depth, required unless the owner waived it
+    pub coinbase_proof: Option<CoinbaseProof>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ExpectedOutput {
+    pub index: u32,
+    /// Least value in satoshis the output must carry
+    pub min_amount: u64,
+    pub script: OutputScript,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CoinbaseProof {
+    pub coinbase_tx: Vec<u8>,
+    pub merkle_proof: Vec<[u8; 32]>,
 }
@@ -0,0 +1,122 @@
+use super::verify_tx::{
+    record_verification, verify_block_header, verify_outputs, verify_recipient,
+    verify_tx_inclusion, CoinbaseProof, ExpectedOutput, ProvenTx,
+};
+use crate::{
+    errors::BtcLightClientError,
+    events::TransactionVerified,
+    state::{BlockHashEntry, BtcLightClientState, HeaderStore, TxBuffer, TxVerifiedState},
+};
+use anchor_lang::prelude::*;
+
+// Same checks as `verify_transaction`, with the raw transaction read from the uploader's
+// buffer, which is closed afterwards
+pub fn verify_transaction_from_buffer(
+    ctx: Context<VerifyTransactionFromBuffer>,
+    block_height: u64,
+    tx_proof: BtcBufferedTxProof,
+) -> Result<()> {
+    let header = verify_block_header(
+        &ctx.accounts.state,
+        block_height,
+        &tx_proof.block_header,
+        ctx.accounts.block_hash_entry.as_deref(),
+        ctx.accounts.header_store.as_ref(),
+    )?;
+
+    // The transaction is parsed and hashed where it lies in the account data
+    let tx_buffer = ctx.accounts.tx_buffer.as_ref().try_borrow_data()?;
+    let tx = verify_tx_inclusion(
+        &ctx.accounts.state,
+        &header,
+        &tx_buffer[TxBuffer::DATA_OFFSET..],
+        tx_proof.tx_id,
+        tx_proof.tx_index,
+        &tx_proof.merkle_proof,
+        tx_proof.coinbase_proof.as_ref(),
+    )?;
+
+    let outputs = verify_outputs(tx.outputs(), &tx_proof.expected_outputs)?;
+    let (recipient, memo) = verify_recipient(tx.outputs(), tx_proof.recipient_output)?;
+    drop(tx_buffer);
+
+    emit!(TransactionVerified {
+        block_height,
+        tx_id: tx_proof.tx_id,
+        amount: outputs.iter().map(|output| output.value).sum(),
+    });
+
+    record_verification(
+        &ctx.accounts.state,
+        &mut ctx.accounts.tx_verified_state,
+        ctx.accounts.payer.key(),
+        block_height,
+        &header,
+        ProvenTx {
+            outputs,
+            recipient,
+            memo,
+            wtxid: [0; 32],
+        },
+    )?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(block_height: u64, tx_proof: BtcBufferedTxProof)]
+pub struct VerifyTransactionFromBuffer<'info> {
+    #[account(
+        seeds = [b"btc_light_client"],
+        bump,
+        constraint = !state.verification_paused @ BtcLightClientError::VerificationPaused
+    )]
+    pub state: Account<'info, BtcLightClientState>,
+
+    #[account(
+        mut,
+        seeds = [b"tx_buffer", payer.key().as_ref(), tx_proof.tx_id.as_ref()],
+        bump,
+        close = payer
+    )]
+    pub tx_buffer: AccountLoader<'info, TxBuffer>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"tx_verified_state".as_ref(), tx_proof.tx_id.as_ref()],
+        bump,
+        payer = payer,
+        space = TxVerifiedState::SPACE
+    )]
+    pub tx_verified_state: Account<'info, TxVerifiedState>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds = [b"block_hash_entry", block_height.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub block_hash_entry: Option<Account<'info, BlockHashEntry>>,
+
+    #[account(
+        constraint = header_store.key() == state.header_store @ BtcLightClientError::InvalidHeaderStore
+    )]
+    pub header_store: Option<AccountLoader<'info, HeaderStore>>,
+}
+
+/// `BtcTxProof` without the raw transaction, which is taken from the `tx_buffer` account
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct BtcBufferedTxProof {
+    pub block_header: Vec<u8>,
+    pub tx_id: [u8; 32],
+    pub tx_index: u32,
+    pub merkle_proof: Vec<[u8; 32]>,
+    /// Outputs to check, by ascending index
+    pub expected_outputs: Vec<ExpectedOutput>,
+    /// OP_RETURN output committing to the Solana recipient of a pegin
+    pub recipient_output: Option<u32>,
+    /// Coinbase proven at index 0 with the same depth, required unless the owner waived it
+    pub coinbase_proof: Option<CoinbaseProof>,
+}
@@ -0,0 +1,149 @@
+use super::verify_tx::{
+    record_verification, verify_block_header, verify_coinbase_proof, verify_outputs,
+    verify_recipient, verify_tx_size, CoinbaseProof, ExpectedOutput, ProvenTx,
+};
+use crate::{
+    errors::BtcLightClientError,
+    events::TransactionWitnessVerified,
+    state::{BlockHashEntry, BtcLightClientState, HeaderStore, TxVerifiedState},
+    transaction::RawTransaction,
+    utils::{
+        index_fits_proof, merkle_root_from_proof, sha256d, witness_commitment_index,
+        MIN_WITNESS_COMMITMENT_SIZE, WITNESS_COMMITMENT_HEADER,
+    },
+};
+use anchor_lang::prelude::*;
+
+pub fn verify_transaction_witness(
+    ctx: Context<VerifyTransactionWitness>,
+    block_height: u64,
+    tx_proof: BtcWitnessTxProof,
+) -> Result<()> {
+    let header = verify_block_header(
+        &ctx.accounts.state,
+        block_height,
+        &tx_proof.block_header,
+        ctx.accounts.block_hash_entry.as_deref(),
+        ctx.accounts.header_store.as_ref(),
+    )?;
+
+    // The coinbase is proven through the txid tree and carries the witness commitment
+    let coinbase = verify_coinbase_proof(
+        &header,
+        &tx_proof.coinbase_proof,
+        tx_proof.witness_merkle_proof.len(),
+    )?;
+
+    let commitment_index =
+        witness_commitment_index(&coinbase).ok_or(BtcLightClientError::InvalidWitnessCommitment)?;
+    let commitment = &coinbase.output[commitment_index].script_pubkey.as_bytes()
+        [WITNESS_COMMITMENT_HEADER.len()..MIN_WITNESS_COMMITMENT_SIZE];
+
+    // The witness reserved value is the single 32-byte item of the coinbase input witness
+    let witness = &coinbase.input[0].witness;
+    let reserved_value = match witness.nth(0) {
+        Some(value) if witness.len() == 1 && value.len() == 32 => value,
+        _ => return err!(BtcLightClientError::InvalidWitnessCommitment),
+    };
+
+    let tx = RawTransaction::parse(&tx_proof.raw_tx)?;
+    require!(
+        tx.txid() == tx_proof.tx_id,
+        BtcLightClientError::TransactionIdMismatch
+    );
+
+    // The witness tree has the txid tree's shape, but the coinbase leaf is zeros
+    require!(
+        tx_proof.tx_index != 0
+            && index_fits_proof(tx_proof.tx_index, &tx_proof.witness_merkle_proof),
+        BtcLightClientError::InvalidMerkleProof
+    );
+    verify_tx_size(tx.base_size())?;
+    let wtxid = tx.wtxid();
+    let witness_root =
+        merkle_root_from_proof(wtxid, tx_proof.tx_index, &tx_proof.witness_merkle_proof);
+
+    require!(
+        sha256d(&[&witness_root, reserved_value]) == commitment,
+        BtcLightClientError::InvalidWitnessCommitment
+    );
+
+    let outputs = verify_outputs(tx.outputs(), &tx_proof.expected_outputs)?;
+    let (recipient, memo) = verify_recipient(tx.outputs(), tx_proof.recipient_output)?;
+
+    emit!(TransactionWitnessVerified {
+        block_height,
+        tx_id: tx_proof.tx_id,
+        wtxid,
+        amount: outputs.iter().map(|output| output.value).sum(),
+    });
+
+    record_verification(
+        &ctx.accounts.state,
+        &mut ctx.accounts.tx_verified_state,
+        ctx.accounts.payer.key(),
+        block_height,
+        &header,
+        ProvenTx {
+            outputs,
+            recipient,
+            memo,
+            wtxid,
+        },
+    )?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(block_height: u64, tx_proof: BtcWitnessTxProof)]
+pub struct VerifyTransactionWitness<'info> {
+    #[account(
+        seeds = [b"btc_light_client"],
+        bump,
+        constraint = !state.verification_paused @ BtcLightClientError::VerificationPaused
+    )]
+    pub state: Account<'info, BtcLightClientState>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"tx_verified_state".as_ref(), tx_proof.tx_id.as_ref()],
+        bump,
+        payer = payer,
+        space = TxVerifiedState::SPACE
+    )]
+    pub tx_verified_state: Account<'info, TxVerifiedState>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds = [b"block_hash_entry", block_height.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub block_hash_entry: Option<Account<'info, BlockHashEntry>>,
+
+    #[account(
+        constraint = header_store.key() == state.header_store @ BtcLightClientError::InvalidHeaderStore
+    )]
+    pub header_store: Option<AccountLoader<'info, HeaderStore>>,
+}
+
+/// Proof of a transaction through the witness tree, which commits to its witness data too
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct BtcWitnessTxProof {
+    pub block_header: Vec<u8>,
+    pub tx_id: [u8; 32],
+    pub tx_index: u32,
+    /// Full serialization including the witness
+    pub raw_tx: Vec<u8>,
+    /// Siblings of the wtxid in the witness merkle tree
+    pub witness_merkle_proof: Vec<[u8; 32]>,
+    /// Coinbase with its witness, proven at index 0 of the txid tree
+    pub coinbase_proof: CoinbaseProof,
+    /// Outputs to check, by ascending index
+    pub expected_outputs: Vec<ExpectedOutput>,
+    /// OP_RETURN output committing to the Solana recipient of a pegin
+    pub recipient_output: Option<u32>,
+}
@@ -2,13 +2,20 @@
 
 use anchor_lang::prelude::*;
 
+pub mod chain;
 pub mod errors;
 pub mod events;
+pub mod header;
 pub mod instructions;
+#[cfg(feature = "cpi")]
+pub mod query;
+pub mod signet;
 pub mod state;
+pub mod transaction;
 pub mod utils;
 
 use instructions::*;
+use state::{ChainContext, Network};
 
 declare_id!("EqriXRhEJRn6FGrhJFchtNL5uGi5cuwQK8aW2SzmRqUc");
 
@@ -21,28 +28,32 @@ pub mod btc_light_client {
         ctx: Context<Initialize>,
         block_height: u64,
         block_hash: [u8; 32],
-        block_time: u32,
         expected_target: [u8; 32],
-        is_testnet: bool,
+        context: ChainContext,
+        network: Network,
         min_confirmations: u64,
     ) -> Result<()> {
         instructions::initialize::initialize(
             ctx,
             block_height,
             block_hash,
-            block_time,
             expected_target,
-            is_testnet,
+            context,
+            network,
             min_confirmations,
         )
     }
 
+    // Move header storage into a pre-allocated ring buffer account
+    pub fn initialize_header_store(ctx: Context<InitializeHeaderStore>) -> Result<()> {
+        instructions::initialize_header_store(ctx)
+    }
+
     pub fn create_block_hash_account(
         ctx: Context<CreateBlockHashAccount>,
         height: u64,
-        block_hash: [u8; 32],
     ) -> Result<()> {
-        instructions::create_block_hash_account(ctx, height, block_hash)
+        instructions::create_block_hash_account(ctx, height)
     }
 
     // Submit new block headers
@@ -54,6 +65,49 @@ pub mod btc_light_client {
         instructions::submit_block_headers(ctx, block_height, headers)
     }
 
+    // Configure the block challenge signet headers are checked against
+    pub fn set_signet_challenge(
+        ctx: Context<SetSignetChallenge>,
+        challenge: Vec<u8>,
+    ) -> Result<()> {
+        instructions::set_signet_challenge(ctx, challenge)
+    }
+
+    // Submit a signet header together with its block solution
+    pub fn submit_signet_header(
+        ctx: Context<SubmitSignetHeader>,
+        block_height: u64,
+        header: Vec<u8>,
+        coinbase_tx: Vec<u8>,
+        coinbase_merkle_proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::submit_signet_header(
+            ctx,
+            block_height,
+            header,
+            coinbase_tx,
+            coinbase_merkle_proof,
+        )
+    }
+
+    // Start uploading a long header batch across several transactions
+    pub fn init_header_buffer(ctx: Context<InitHeaderBuffer>, start_height: u64) -> Result<()> {
+        instructions::init_header_buffer(ctx, start_height)
+    }
+
+    pub fn append_header_buffer(ctx: Context<AppendHeaderBuffer>, headers: Vec<u8>) -> Result<()> {
+        instructions::append_header_buffer(ctx, headers)
+    }
+
+    // Validate and apply all buffered headers at once
+    pub fn finalize_header_buffer(ctx: Context<FinalizeHeaderBuffer>) -> Result<()> {
+        instructions::finalize_header_buffer(ctx)
+    }
+
+    pub fn close_header_buffer(ctx: Context<CloseHeaderBuffer>) -> Result<()> {
+        instructions::close_header_buffer(ctx)
+    }
+
     // Verify Bitcoin transaction
     pub fn verify_transaction(
         ctx: Context<VerifyTransaction>,
@@ -63,10 +117,197 @@ pub mod btc_light_client {
         instructions::verify_tx::verify_transaction(ctx, block_height, tx_proof)
     }
 
+    // Start uploading a raw transaction too large for a single `verify_transaction` call
+    pub fn init_tx_buffer(ctx: Context<InitTxBuffer>, tx_id: [u8; 32]) -> Result<()> {
+        instructions::init_tx_buffer(ctx, tx_id)
+    }
+
+    pub fn append_tx_buffer(
+        ctx: Context<AppendTxBuffer>,
+        tx_id: [u8; 32],
+        offset: u32,
+        chunk: Vec<u8>,
+    ) -> Result<()> {
+        instructions::append_tx_buffer(ctx, tx_id, offset, chunk)
+    }
+
+    pub fn close_tx_buffer(ctx: Context<CloseTxBuffer>, tx_id: [u8; 32]) -> Result<()> {
+        instructions::close_tx_buffer(ctx, tx_id)
+    }
+
+    // Verify a Bitcoin transaction uploaded into a tx buffer
+    pub fn verify_transaction_from_buffer(
+        ctx: Context<VerifyTransactionFromBuffer>,
+        block_height: u64,
+        tx_proof: BtcBufferedTxProof,
+    ) -> Result<()> {
+        instructions::verify_transaction_from_buffer(ctx, block_height, tx_proof)
+    }
+
+    // Verify Bitcoin transaction through its wtxid and the block's witness commitment
+    pub fn verify_transaction_witness(
+        ctx: Context<VerifyTransactionWitness>,
+        block_height: u64,
+        tx_proof: BtcWitnessTxProof,
+    ) -> Result<()> {
+        instructions::verify_transaction_witness(ctx, block_height, tx_proof)
+    }
+
+    // Verify that a Bitcoin transaction spent a given outpoint
+    pub fn verify_spend(
+        ctx: Context<VerifySpend>,
+        block_height: u64,
+        spend_proof: BtcSpendProof,
+    ) -> Result<()> {
+        instructions::verify_spend(ctx, block_height, spend_proof)
+    }
+
+    // Query instructions, results are returned through `set_return_data`
+    pub fn get_tip(ctx: Context<GetTip>) -> Result<ChainTip> {
+        instructions::get_tip(ctx)
+    }
+
+    pub fn get_block_hash(ctx: Context<GetBlockHash>, block_height: u64) -> Result<[u8; 32]> {
+        instructions::get_block_hash(ctx, block_height)
+    }
+
+    pub fn get_tx_confirmations(ctx: Context<GetTxConfirmations>, tx_id: [u8; 32]) -> Result<u64> {
+        instructions::get_tx_confirmations(ctx, tx_id)
+    }
+
+    pub fn get_median_time_past(ctx: Context<GetMedianTimePast>) -> Result<u32> {
+        instructions::get_median_time_past(ctx)
+    }
+
     pub fn update_min_confirmations(
         ctx: Context<UpdateMinConfirmations>,
         min_confirmations: u64,
     ) -> Result<()> {
         instructions::update_min_confirmations(ctx, min_confirmations)
     }
+
+    pub fn update_permissionless_relaying(
+        ctx: Context<UpdatePermissionlessRelaying>,
+        enabled: bool,
+    ) -> Result<()> {
+        instructions::update_permissionless_relaying(ctx, enabled)
+    }
+
+    pub fn update_relayer_reward(
+        ctx: Context<UpdateRelayerReward>,
+        relayer_reward: u64,
+    ) -> Result<()> {
+        instructions::update_relayer_reward(ctx, relayer_reward)
+    }
+
+    pub fn update_require_coinbase_proof(
+        ctx: Context<UpdateRequireCoinbaseProof>,
+        enabled: bool,
+    ) -> Result<()> {
+        instructions::update_require_coinbase_proof(ctx, enabled)
+    }
+
+    pub fn update_max_reorg_depth(
+        ctx: Context<UpdateMaxReorgDepth>,
+        max_reorg_depth: u64,
+    ) -> Result<()> {
+        instructions::update_max_reorg_depth(ctx, max_reorg_depth)
+    }
+
+    // Pin a block of the current chain as final
+    pub fn set_checkpoint(
+        ctx: Context<SetCheckpoint>,
+        block_height: u64,
+        block_hash: [u8; 32],
+    ) -> Result<()> {
+        instructions::set_checkpoint(ctx, block_height, block_hash)
+    }
+
+    // Emergency stops for incident response
+    pub fn pause_submission(ctx: Context<ToggleSubmissionPause>) -> Result<()> {
+        instructions::pause_submission(ctx)
+    }
+
+    pub fn unpause_submission(ctx: Context<ToggleSubmissionPause>) -> Result<()> {
+        instructions::unpause_submission(ctx)
+    }
+
+    pub fn pause_verification(ctx: Context<ToggleVerificationPause>) -> Result<()> {
+        instructions::pause_verification(ctx)
+    }
+
+    pub fn unpause_verification(ctx: Context<ToggleVerificationPause>) -> Result<()> {
+        instructions::unpause_verification(ctx)
+    }
+
+    // Reallocs state created by an older program version to the current layout
+    pub fn migrate_state(
+        ctx: Context<MigrateState>,
+        context: ChainContext,
+        network: Network,
+    ) -> Result<()> {
+        instructions::migrate_state(ctx, context, network)
+    }
+
+    pub fn migrate_block_hash_entry(
+        ctx: Context<MigrateBlockHashEntry>,
+        block_height: u64,
+        context: ChainContext,
+        period_target: [u8; 32],
+        bits: u32,
+    ) -> Result<()> {
+        instructions::migrate_block_hash_entry(ctx, block_height, context, period_target, bits)
+    }
+
+    pub fn migrate_tx_verified_state(
+        ctx: Context<MigrateTxVerifiedState>,
+        tx_id: [u8; 32],
+    ) -> Result<()> {
+        instructions::migrate_tx_verified_state(ctx, tx_id)
+    }
+
+    pub fn add_submitter(ctx: Context<AddSubmitter>, submitter: Pubkey) -> Result<()> {
+        instructions::add_submitter(ctx, submitter)
+    }
+
+    pub fn remove_submitter(ctx: Context<RemoveSubmitter>, submitter: Pubkey) -> Result<()> {
+        instructions::remove_submitter(ctx, submitter)
+    }
+
+    // Two-step transfer so the owner key can't be handed to an account nobody controls
+    pub fn propose_ownership(ctx: Context<ProposeOwnership>, new_owner: Pubkey) -> Result<()> {
+        instructions::propose_ownership(ctx, new_owner)
+    }
+
+    pub fn accept_ownership(ctx: Context<AcceptOwnership>) -> Result<()> {
+        instructions::accept_ownership(ctx)
+    }
+
+    pub fn fund_relayer_rewards(ctx: Context<FundRelayerRewards>, amount: u64) -> Result<()> {
+        instructions::fund_relayer_rewards(ctx, amount)
+    }
+
+    pub fn update_block_entry_retention(
+        ctx: Context<UpdateBlockEntryRetention>,
+        block_entry_retention: u64,
+    ) -> Result<()> {
+        instructions::update_block_entry_retention(ctx, block_entry_retention)
+    }
+
+    pub fn update_tx_consumer(ctx: Context<UpdateTxConsumer>, tx_consumer: Pubkey) -> Result<()> {
+        instructions::update_tx_consumer(ctx, tx_consumer)
+    }
+
+    // Close a block hash entry that fell behind the retention depth, refunding its payer
+    pub fn close_block_hash_entry(ctx: Context<CloseBlockHashEntry>, height: u64) -> Result<()> {
+        instructions::close_block_hash_entry(ctx, height)
+    }
+
+    // Close a verified tx state once the consumer has used it, refunding its payer
+    pub fn close_tx_verified_state(
+        ctx: Context<CloseTxVerifiedState>,
+        tx_id: [u8; 32],
+    ) -> Result<()> {
+        instructions::close_tx_verified_state(ctx, tx_id)
+    }
 }
@@ -0,0 +1,68 @@
+//! Typed wrappers around the query instructions for programs calling the light client
+//! through CPI, so they read light client facts without depending on its account layouts
+
+use crate::{
+    cpi::{self, accounts},
+    instructions::ChainTip,
+};
+use anchor_lang::prelude::*;
+
+/// Latest block of the heaviest verified chain
+pub fn get_tip<'info>(program: AccountInfo<'info>, state: AccountInfo<'info>) -> Result<ChainTip> {
+    Ok(cpi::get_tip(CpiContext::new(program, accounts::GetTip { state }))?.get())
+}
+
+/// Hash of the verified block at `block_height`. `block_hash_entry` is the entry account of
+/// that height, `header_store` the attached header store; pass whichever the client uses.
+pub fn get_block_hash<'info>(
+    program: AccountInfo<'info>,
+    state: AccountInfo<'info>,
+    block_hash_entry: Option<AccountInfo<'info>>,
+    header_store: Option<AccountInfo<'info>>,
+    block_height: u64,
+) -> Result<[u8; 32]> {
+    let ctx = CpiContext::new(
+        program,
+        accounts::GetBlockHash {
+            state,
+            block_hash_entry,
+            header_store,
+        },
+    );
+    Ok(cpi::get_block_hash(ctx, block_height)?.get())
+}
+
+/// Confirmations of the verified transaction `tx_id`, zero if its block is no longer canonical.
+/// A transaction proven once its block was final needs neither `block_hash_entry` nor
+/// `header_store`.
+pub fn get_tx_confirmations<'info>(
+    program: AccountInfo<'info>,
+    state: AccountInfo<'info>,
+    tx_verified_state: AccountInfo<'info>,
+    block_hash_entry: Option<AccountInfo<'info>>,
+    header_store: Option<AccountInfo<'info>>,
+    tx_id: [u8; 32],
+) -> Result<u64> {
+    let ctx = CpiContext::new(
+        program,
+        accounts::GetTxConfirmations {
+            state,
+            tx_verified_state,
+            block_hash_entry,
+            header_store,
+        },
+    );
+    Ok(cpi::get_tx_confirmations(ctx, tx_id)?.get())
+}
+
+/// Median time past of the tip, the lower bound for the next block's timestamp
+pub fn get_median_time_past<'info>(
+    program: AccountInfo<'info>,
+    state: AccountInfo<'info>,
+) -> Result<u32> {
+    Ok(cpi::get_median_time_past(CpiContext::new(
+        program,
+        accounts::GetMedianTimePast { state },
+    ))?
+    .get())
+}
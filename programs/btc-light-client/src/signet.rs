@@ -0,0 +1,479 @@
+use crate::{
+    errors::BtcLightClientError,
+    header::RawHeader,
+    utils::{merkle_root_from_proof, txid, verify_merkle_proof, witness_commitment_index},
+};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::secp256k1_recover::secp256k1_recover;
+use bitcoin::{
+    absolute::LockTime,
+    consensus::encode::{deserialize, deserialize_partial},
+    hashes::Hash,
+    opcodes::all::{OP_CHECKMULTISIG, OP_CHECKSIG, OP_PUSHNUM_1, OP_PUSHNUM_16, OP_RETURN},
+    script::{Builder, Instruction, PushBytesBuf},
+    sighash::SighashCache,
+    transaction::Version,
+    Amount, OutPoint, Script, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness,
+};
+
+/// Marks the signet solution inside the coinbase witness commitment output (BIP325)
+pub const SIGNET_HEADER: [u8; 4] = [0xec, 0xc7, 0xda, 0xa2];
+/// Upper bound on keys in a CHECKMULTISIG challenge
+const MAX_PUBKEYS_PER_MULTISIG: usize = 20;
+
+/// Signet block challenges the light client can check. Both are bare scripts signed with
+/// ECDSA, the form used by the public signet.
+pub enum SignetChallenge<'a> {
+    /// `<pubkey> OP_CHECKSIG`
+    Single(&'a [u8]),
+    /// `OP_k <pubkey>... OP_n OP_CHECKMULTISIG`
+    Multi {
+        required: usize,
+        pubkeys: Vec<&'a [u8]>,
+    },
+}
+
+impl<'a> SignetChallenge<'a> {
+    pub fn parse(script: &'a Script) -> Result<Self> {
+        let instructions = script
+            .instructions()
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|_| BtcLightClientError::UnsupportedSignetChallenge)?;
+
+        match instructions.as_slice() {
+            [Instruction::PushBytes(pubkey), Instruction::Op(op)] if *op == OP_CHECKSIG => {
+                Ok(SignetChallenge::Single(pubkey.as_bytes()))
+            }
+            [Instruction::Op(k), keys @ .., Instruction::Op(n), Instruction::Op(op)]
+                if *op == OP_CHECKMULTISIG =>
+            {
+                let required =
+                    small_int(*k).ok_or(BtcLightClientError::UnsupportedSignetChallenge)?;
+                let total = small_int(*n).ok_or(BtcLightClientError::UnsupportedSignetChallenge)?;
+                let pubkeys = keys
+                    .iter()
+                    .map(|key| match key {
+                        Instruction::PushBytes(pubkey) => Some(pubkey.as_bytes()),
+                        Instruction::Op(_) => None,
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or(BtcLightClientError::UnsupportedSignetChallenge)?;
+                require!(
+                    required <= total
+                        && total == pubkeys.len()
+                        && total <= MAX_PUBKEYS_PER_MULTISIG,
+                    BtcLightClientError::UnsupportedSignetChallenge
+                );
+                Ok(SignetChallenge::Multi { required, pubkeys })
+            }
+            _ => err!(BtcLightClientError::UnsupportedSignetChallenge),
+        }
+    }
+}
+
+/// Checks the signet block solution of `header`, following Bitcoin Core's
+/// `CheckSignetBlockSolution`: the solution is taken from the coinbase witness commitment, and
+/// must satisfy `challenge` when spending a virtual output that commits to the header with
+/// the solution stripped from the merkle root.
+pub fn verify_signet_solution(
+    header: &RawHeader,
+    coinbase_tx: &[u8],
+    coinbase_merkle_proof: &[[u8; 32]],
+    challenge: &Script,
+) -> Result<()> {
+    let mut coinbase: Transaction =
+        deserialize(coinbase_tx).map_err(|_| BtcLightClientError::InvalidTransactionFormat)?;
+    require!(
+        coinbase.is_coinbase(),
+        BtcLightClientError::InvalidSignetSolution
+    );
+    require!(
+        verify_merkle_proof(
+            txid(&coinbase),
+            header.merkle_root(),
+            0,
+            coinbase_merkle_proof
+        ),
+        BtcLightClientError::InvalidMerkleProof
+    );
+
+    // Take the solution out of the last witness commitment output
+    let commitment_index =
+        witness_commitment_index(&coinbase).ok_or(BtcLightClientError::InvalidSignetSolution)?;
+    let solution =
+        fetch_and_clear_commitment_section(&mut coinbase.output[commitment_index].script_pubkey)?
+            .ok_or(BtcLightClientError::InvalidSignetSolution)?;
+
+    let (script_sig, consumed): (ScriptBuf, usize) =
+        deserialize_partial(&solution).map_err(|_| BtcLightClientError::InvalidSignetSolution)?;
+    let witness: Witness = deserialize(&solution[consumed..])
+        .map_err(|_| BtcLightClientError::InvalidSignetSolution)?;
+    // Bare challenges take no witness
+    require!(
+        witness.is_empty(),
+        BtcLightClientError::InvalidSignetSolution
+    );
+
+    // The merkle root of the block with the solution removed from the coinbase
+    let signet_merkle_root = merkle_root_from_proof(txid(&coinbase), 0, coinbase_merkle_proof);
+
+    // Version, previous block hash, the signet merkle root and the timestamp
+    let header_bytes = header.as_bytes();
+    let mut block_data = Vec::with_capacity(72);
+    block_data.extend_from_slice(&header_bytes[..36]);
+    block_data.extend_from_slice(&signet_merkle_root);
+    block_data.extend_from_slice(&header_bytes[68..72]);
+
+    let stack = push_only_stack(&script_sig)?;
+    let to_sign = signet_spending_tx(block_data, challenge, script_sig)?;
+    let sighash_cache = SighashCache::new(&to_sign);
+    let check_sig = |signature: &[u8], pubkey: &[u8]| -> bool {
+        let Some((&sighash_type, der)) = signature.split_last() else {
+            return false;
+        };
+        let Some(compact) = parse_der_signature(der) else {
+            return false;
+        };
+        sighash_cache
+            .legacy_signature_hash(0, challenge, sighash_type as u32)
+            .map(|sighash| recovers_to(&sighash.to_byte_array(), &compact, pubkey))
+            .unwrap_or(false)
+    };
+
+    let valid = match SignetChallenge::parse(challenge)? {
+        SignetChallenge::Single(pubkey) => stack
+            .last()
+            .is_some_and(|signature| check_sig(signature, pubkey)),
+        SignetChallenge::Multi { required, pubkeys } => {
+            // CHECKMULTISIG pops the signatures and an empty dummy element (NULLDUMMY)
+            match stack.len().checked_sub(required + 1) {
+                Some(dummy) if stack[dummy].is_empty() => {
+                    let signatures = &stack[dummy + 1..];
+                    let mut keys = pubkeys.iter();
+                    signatures
+                        .iter()
+                        .all(|signature| keys.any(|pubkey| check_sig(signature, pubkey)))
+                }
+                _ => false,
+            }
+        }
+    };
+    require!(valid, BtcLightClientError::InvalidSignetSolution);
+
+    Ok(())
+}
+
+/// The virtual transaction whose signature hash the solution signs: it spends, with
+/// `script_sig`, an output locked by `challenge` that commits to `block_data`
+fn signet_spending_tx(
+    block_data: Vec<u8>,
+    challenge: &Script,
+    script_sig: ScriptBuf,
+) -> Result<Transaction> {
+    let to_spend = Transaction {
+        version: Version::non_standard(0),
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::null(),
+            script_sig: Builder::new()
+                .push_slice([])
+                .push_slice(
+                    PushBytesBuf::try_from(block_data)
+                        .map_err(|_| BtcLightClientError::InvalidSignetSolution)?,
+                )
+                .into_script(),
+            sequence: Sequence(0),
+            witness: Witness::new(),
+        }],
+        output: vec![TxOut {
+            value: Amount::ZERO,
+            script_pubkey: challenge.to_owned(),
+        }],
+    };
+    Ok(Transaction {
+        version: Version::non_standard(0),
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::new(to_spend.txid(), 0),
+            script_sig,
+            sequence: Sequence(0),
+            witness: Witness::new(),
+        }],
+        output: vec![TxOut {
+            value: Amount::ZERO,
+            script_pubkey: Builder::new().push_opcode(OP_RETURN).into_script(),
+        }],
+    })
+}
+
+/// Removes the data following `SIGNET_HEADER` from the first push carrying it and returns
+/// that data, rebuilding the script like Bitcoin Core's `FetchAndClearCommitmentSection`
+fn fetch_and_clear_commitment_section(script: &mut ScriptBuf) -> Result<Option<Vec<u8>>> {
+    let mut replacement = Builder::new();
+    let mut result = None;
+
+    for instruction in script.instructions() {
+        match instruction.map_err(|_| BtcLightClientError::InvalidSignetSolution)? {
+            Instruction::PushBytes(data) if !data.is_empty() => {
+                let bytes = data.as_bytes();
+                if result.is_none()
+                    && bytes.len() > SIGNET_HEADER.len()
+                    && bytes.starts_with(&SIGNET_HEADER)
+                {
+                    result = Some(bytes[SIGNET_HEADER.len()..].to_vec());
+                    replacement = replacement.push_slice(SIGNET_HEADER);
+                } else {
+                    replacement = replacement.push_slice(data);
+                }
+            }
+            Instruction::PushBytes(_) => replacement = replacement.push_slice([]),
+            Instruction::Op(op) => replacement = replacement.push_opcode(op),
+        }
+    }
+
+    if result.is_some() {
+        *script = replacement.into_script();
+    }
+    Ok(result)
+}
+
+/// Stack left by a script made only of data pushes and small integers
+fn push_only_stack(script: &Script) -> Result<Vec<Vec<u8>>> {
+    script
+        .instructions()
+        .map(|instruction| match instruction {
+            Ok(Instruction::PushBytes(data)) => Ok(data.as_bytes().to_vec()),
+            Ok(Instruction::Op(op)) => small_int(op)
+                .map(|n| vec![n as u8])
+                .ok_or(error!(BtcLightClientError::InvalidSignetSolution)),
+            Err(_) => err!(BtcLightClientError::InvalidSignetSolution),
+        })
+        .collect()
+}
+
+/// Value of an `OP_1`..`OP_16` opcode
+fn small_int(op: bitcoin::Opcode) -> Option<usize> {
+    (OP_PUSHNUM_1.to_u8()..=OP_PUSHNUM_16.to_u8())
+        .contains(&op.to_u8())
+        .then(|| (op.to_u8() - OP_PUSHNUM_1.to_u8() + 1) as usize)
+}
+
+/// Extracts the 64-byte `r || s` form of a strictly DER encoded signature (BIP66)
+fn parse_der_signature(der: &[u8]) -> Option<[u8; 64]> {
+    // 0x30 [total-length] 0x02 [R-length] [R] 0x02 [S-length] [S]
+    if der.len() < 8 || der.len() > 72 || der[0] != 0x30 || der[1] as usize != der.len() - 2 {
+        return None;
+    }
+    let r_len = der[3] as usize;
+    if der[2] != 0x02 || r_len == 0 || 5 + r_len >= der.len() {
+        return None;
+    }
+    let s_len = der[5 + r_len] as usize;
+    if der[4 + r_len] != 0x02 || s_len == 0 || r_len + s_len + 6 != der.len() {
+        return None;
+    }
+
+    let mut compact = [0u8; 64];
+    for (i, int) in [&der[4..4 + r_len], &der[6 + r_len..]]
+        .into_iter()
+        .enumerate()
+    {
+        // Integers are positive and minimally encoded
+        if int[0] & 0x80 != 0 || (int.len() > 1 && int[0] == 0 && int[1] & 0x80 == 0) {
+            return None;
+        }
+        let int = if int[0] == 0 { &int[1..] } else { int };
+        if int.len() > 32 {
+            return None;
+        }
+        compact[32 * i + 32 - int.len()..32 * (i + 1)].copy_from_slice(int);
+    }
+    Some(compact)
+}
+
+/// Whether `signature` over `message` was made by `pubkey`, checked by recovering the signer
+/// through the secp256k1 syscall
+fn recovers_to(message: &[u8; 32], signature: &[u8; 64], pubkey: &[u8]) -> bool {
+    (0..2).any(|recovery_id| {
+        secp256k1_recover(message, recovery_id, signature).is_ok_and(|recovered| {
+            let recovered = recovered.to_bytes();
+            match pubkey {
+                [0x04, uncompressed @ ..] => uncompressed == recovered,
+                [prefix @ (0x02 | 0x03), x @ ..] => {
+                    *x == recovered[..32] && *prefix == 0x02 | (recovered[63] & 1)
+                }
+                _ => false,
+            }
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::{
+        consensus::encode::serialize,
+        secp256k1::{Message, Secp256k1, SecretKey},
+        sighash::EcdsaSighashType,
+    };
+
+    /// First key of the public signet's 1-of-2 challenge, which these tests cannot sign for
+    const SIGNET_PUBKEY: [u8; 33] = [
+        0x03, 0xad, 0x5e, 0x0e, 0xda, 0xd1, 0x8c, 0xb1, 0xf0, 0xfc, 0x0d, 0x28, 0xa3, 0xd4, 0xf1,
+        0xf3, 0xe4, 0x45, 0x64, 0x03, 0x37, 0x48, 0x9a, 0xbb, 0x10, 0x40, 0x4f, 0x2d, 0x1e, 0x08,
+        0x6b, 0xe4, 0x30,
+    ];
+    /// A transaction after the coinbase, so the coinbase proof has one step
+    const SIBLING_TXID: [u8; 32] = [0x5a; 32];
+
+    struct SignetBlock {
+        header: [u8; 80],
+        coinbase: Vec<u8>,
+        proof: Vec<[u8; 32]>,
+        challenge: ScriptBuf,
+    }
+
+    fn coinbase_with_commitment(section: &[u8]) -> Transaction {
+        let mut commitment = vec![0xaa, 0x21, 0xa9, 0xed];
+        commitment.extend_from_slice(&[0x11; 32]);
+        Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: Builder::new().push_int(230_628).into_script(),
+                sequence: Sequence::MAX,
+                witness: Witness::from_slice(&[[0u8; 32]]),
+            }],
+            output: vec![
+                TxOut {
+                    value: Amount::from_sat(5_000_000_000),
+                    script_pubkey: Builder::new().push_opcode(OP_PUSHNUM_1).into_script(),
+                },
+                TxOut {
+                    value: Amount::ZERO,
+                    script_pubkey: Builder::new()
+                        .push_opcode(OP_RETURN)
+                        .push_slice(<[u8; 36]>::try_from(commitment).unwrap())
+                        .push_slice(PushBytesBuf::try_from(section.to_vec()).unwrap())
+                        .into_script(),
+                },
+            ],
+        }
+    }
+
+    fn header(merkle_root: [u8; 32]) -> [u8; 80] {
+        let mut header = [0u8; 80];
+        header[..4].copy_from_slice(&0x2000_0000u32.to_le_bytes());
+        header[4..36].copy_from_slice(&[0x22; 32]);
+        header[36..68].copy_from_slice(&merkle_root);
+        header[68..72].copy_from_slice(&1_736_000_000u32.to_le_bytes());
+        header[72..76].copy_from_slice(&0x1e03_77aeu32.to_le_bytes());
+        header
+    }
+
+    /// Builds a block signed, in the public signet's solution format, by a key standing in
+    /// for the second key of a 1-of-2 challenge shaped like the public signet's
+    fn signed_block(tamper_signature: fn(&mut Vec<u8>)) -> SignetBlock {
+        let secp = Secp256k1::signing_only();
+        let secret_key = SecretKey::from_slice(&[0x42; 32]).unwrap();
+        let pubkey = secret_key.public_key(&secp).serialize();
+        let challenge = Builder::new()
+            .push_opcode(OP_PUSHNUM_1)
+            .push_slice(SIGNET_PUBKEY)
+            .push_slice(pubkey)
+            .push_opcode(bitcoin::opcodes::all::OP_PUSHNUM_2)
+            .push_opcode(OP_CHECKMULTISIG)
+            .into_script();
+        let proof = vec![SIBLING_TXID];
+
+        // The solution signs the block as if the coinbase carried none
+        let unsigned = coinbase_with_commitment(&SIGNET_HEADER);
+        let signet_merkle_root = merkle_root_from_proof(txid(&unsigned), 0, &proof);
+        let unsigned_header = header(signet_merkle_root);
+        let mut block_data = unsigned_header[..36].to_vec();
+        block_data.extend_from_slice(&signet_merkle_root);
+        block_data.extend_from_slice(&unsigned_header[68..72]);
+
+        let to_sign = signet_spending_tx(block_data, &challenge, ScriptBuf::new()).unwrap();
+        let sighash = SighashCache::new(&to_sign)
+            .legacy_signature_hash(0, &challenge, EcdsaSighashType::All.to_u32())
+            .unwrap();
+        let signature =
+            secp.sign_ecdsa(&Message::from_digest(sighash.to_byte_array()), &secret_key);
+        let mut signature = signature.serialize_der().to_vec();
+        signature.push(EcdsaSighashType::All.to_u32() as u8);
+        tamper_signature(&mut signature);
+
+        // OP_0 dummy for CHECKMULTISIG, then the signature, followed by an empty witness
+        let script_sig = Builder::new()
+            .push_slice([])
+            .push_slice(PushBytesBuf::try_from(signature).unwrap())
+            .into_script();
+        let mut section = SIGNET_HEADER.to_vec();
+        section.extend(serialize(&script_sig));
+        section.extend(serialize(&Witness::new()));
+
+        let coinbase = coinbase_with_commitment(&section);
+        let merkle_root = merkle_root_from_proof(txid(&coinbase), 0, &proof);
+        SignetBlock {
+            header: header(merkle_root),
+            coinbase: serialize(&coinbase),
+            proof,
+            challenge,
+        }
+    }
+
+    fn verify(block: &SignetBlock) -> Result<()> {
+        verify_signet_solution(
+            &RawHeader::parse(&block.header).unwrap(),
+            &block.coinbase,
+            &block.proof,
+            &block.challenge,
+        )
+    }
+
+    #[test]
+    fn accepts_a_signed_block() {
+        let block = signed_block(|_| {});
+        assert!(matches!(
+            SignetChallenge::parse(&block.challenge).unwrap(),
+            SignetChallenge::Multi { required: 1, ref pubkeys } if pubkeys.len() == 2
+        ));
+        verify(&block).unwrap();
+    }
+
+    #[test]
+    fn rejects_a_tampered_signature() {
+        // flip the last byte of `s`, just before the sighash type
+        let block = signed_block(|signature| {
+            let last_s = signature.len() - 2;
+            signature[last_s] ^= 0x01;
+        });
+        assert_eq!(
+            verify(&block).unwrap_err(),
+            BtcLightClientError::InvalidSignetSolution.into()
+        );
+    }
+
+    #[test]
+    fn rejects_a_tampered_header() {
+        let mut block = signed_block(|_| {});
+        // a later timestamp is part of the signed block data
+        block.header[68] ^= 0x01;
+        assert_eq!(
+            verify(&block).unwrap_err(),
+            BtcLightClientError::InvalidSignetSolution.into()
+        );
+    }
+
+    #[test]
+    fn rejects_a_coinbase_outside_the_block() {
+        let mut block = signed_block(|_| {});
+        block.header[36] ^= 0x01;
+        assert_eq!(
+            verify(&block).unwrap_err(),
+            BtcLightClientError::InvalidMerkleProof.into()
+        );
+    }
+}
@@ -1,7 +1,221 @@
+use crate::{
+    errors::BtcLightClientError,
+    utils::{median_time_past, MEDIAN_TIME_SPAN},
+};
 use anchor_lang::prelude::*;
+use bitcoin::{
+    opcodes::all::{
+        OP_CHECKSIG, OP_DUP, OP_EQUAL, OP_EQUALVERIFY, OP_HASH160, OP_PUSHBYTES_0, OP_PUSHNUM_1,
+    },
+    pow::Target,
+    script::Builder,
+    ScriptBuf,
+};
+
+/// Bitcoin network whose consensus rules the light client follows
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Network {
+    Mainnet,
+    Testnet3,
+    Testnet4,
+    Signet,
+    Regtest,
+}
+
+impl Network {
+    /// Highest target, i.e. lowest difficulty, a block may have
+    pub fn pow_limit(&self) -> Target {
+        match self {
+            Network::Mainnet => Target::MAX_ATTAINABLE_MAINNET,
+            Network::Testnet3 | Network::Testnet4 => Target::MAX_ATTAINABLE_TESTNET,
+            Network::Signet => Target::MAX_ATTAINABLE_SIGNET,
+            Network::Regtest => Target::MAX_ATTAINABLE_REGTEST,
+        }
+    }
+
+    /// Whether a block more than twenty minutes after its parent uses minimum difficulty
+    pub fn allows_min_difficulty_blocks(&self) -> bool {
+        matches!(
+            self,
+            Network::Testnet3 | Network::Testnet4 | Network::Regtest
+        )
+    }
+
+    /// Whether difficulty stays the same across retargets
+    pub fn no_retargeting(&self) -> bool {
+        *self == Network::Regtest
+    }
+}
+
+/// Chain context of a trusted block that header validation needs to accept its children
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct ChainContext {
+    /// Timestamp of the block
+    pub block_time: u32,
+    /// Timestamps of its ten predecessors, oldest first
+    pub prev_block_times: [u32; 10],
+    /// Cumulative chainwork up to and including the block (big-endian)
+    pub chainwork: [u8; 32],
+    /// Timestamp of the first block in the block's difficulty period
+    pub period_start_time: u32,
+}
+
+impl ChainContext {
+    /// Timestamps of the block and its ten predecessors, oldest first
+    pub fn recent_block_times(&self) -> [u32; MEDIAN_TIME_SPAN] {
+        let mut recent_block_times = [0; MEDIAN_TIME_SPAN];
+        recent_block_times[..self.prev_block_times.len()].copy_from_slice(&self.prev_block_times);
+        recent_block_times[MEDIAN_TIME_SPAN - 1] = self.block_time;
+        recent_block_times
+    }
+}
+
+/// Output script a proven transaction output must pay to
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum OutputScript {
+    P2pkh {
+        pubkey_hash: [u8; 20],
+    },
+    P2sh {
+        script_hash: [u8; 20],
+    },
+    P2wpkh {
+        pubkey_hash: [u8; 20],
+    },
+    P2wsh {
+        script_hash: [u8; 32],
+    },
+    P2tr {
+        output_key: [u8; 32],
+    },
+    /// Any other script_pubkey, matched byte for byte
+    Exact {
+        script: Vec<u8>,
+    },
+}
+
+impl OutputScript {
+    /// The script_pubkey this descriptor stands for
+    pub fn to_script_pubkey(&self) -> ScriptBuf {
+        match self {
+            OutputScript::P2pkh { pubkey_hash } => Builder::new()
+                .push_opcode(OP_DUP)
+                .push_opcode(OP_HASH160)
+                .push_slice(pubkey_hash)
+                .push_opcode(OP_EQUALVERIFY)
+                .push_opcode(OP_CHECKSIG)
+                .into_script(),
+            OutputScript::P2sh { script_hash } => Builder::new()
+                .push_opcode(OP_HASH160)
+                .push_slice(script_hash)
+                .push_opcode(OP_EQUAL)
+                .into_script(),
+            OutputScript::P2wpkh { pubkey_hash } => Builder::new()
+                .push_opcode(OP_PUSHBYTES_0)
+                .push_slice(pubkey_hash)
+                .into_script(),
+            OutputScript::P2wsh { script_hash } => Builder::new()
+                .push_opcode(OP_PUSHBYTES_0)
+                .push_slice(script_hash)
+                .into_script(),
+            OutputScript::P2tr { output_key } => Builder::new()
+                .push_opcode(OP_PUSHNUM_1)
+                .push_slice(output_key)
+                .into_script(),
+            OutputScript::Exact { script } => ScriptBuf::from_bytes(script.clone()),
+        }
+    }
+}
+
+/// Layout version written by `initialize` and `migrate_state`, stored right after the
+/// discriminator. Accounts created before versioning have the `LegacyBtcLightClientState`
+/// layout, read as version 0; `migrate_state` reallocs them to `BtcLightClientState::SPACE`
+/// and fills in every field they lack.
+pub const STATE_VERSION: u8 = 1;
+
+/// Light client state as deployed before versioning. `network` later took over the
+/// `is_testnet` byte, every other field was appended after `owner`.
+#[derive(AnchorDeserialize)]
+pub struct LegacyBtcLightClientState {
+    pub latest_block_hash: [u8; 32],
+    pub latest_block_height: u64,
+    pub latest_block_time: u32,
+    pub latest_period_target: [u8; 32],
+    pub is_testnet: bool,
+    pub min_confirmations: u64,
+    pub owner: Pubkey,
+}
+
+impl LegacyBtcLightClientState {
+    pub const SPACE: usize = 8 +  // discriminator
+        32 +  // latest_block_hash
+        8 +  // latest_block_height
+        4 +  // latest_block_time
+        32 +  // latest_period_target
+        1 +  // is_testnet
+        8 +  // min_confirmations
+        32; // owner
+
+    /// Reads the state from account data in the legacy layout
+    pub fn try_from_account_data(data: &[u8]) -> Result<Self> {
+        read_legacy_account(data, Self::SPACE, BtcLightClientState::DISCRIMINATOR)
+    }
+}
+
+/// Block hash entry as deployed before versioning, without the chain context
+#[derive(AnchorDeserialize)]
+pub struct LegacyBlockHashEntry {
+    pub height: u64,
+    pub hash: [u8; 32],
+}
+
+impl LegacyBlockHashEntry {
+    pub const SPACE: usize = 8 + 8 + 32; // discriminator + height + hash
+
+    /// Reads the entry from account data in the legacy layout
+    pub fn try_from_account_data(data: &[u8]) -> Result<Self> {
+        read_legacy_account(data, Self::SPACE, BlockHashEntry::DISCRIMINATOR)
+    }
+}
+
+/// Verification record as deployed before versioning, holding only the verified flag
+#[derive(AnchorDeserialize)]
+pub struct LegacyTxVerifiedState {
+    pub is_verified: bool,
+}
+
+impl LegacyTxVerifiedState {
+    pub const SPACE: usize = 8 + 1; // discriminator + is_verified
+
+    /// Reads the record from account data in the legacy layout
+    pub fn try_from_account_data(data: &[u8]) -> Result<Self> {
+        read_legacy_account(data, Self::SPACE, TxVerifiedState::DISCRIMINATOR)
+    }
+}
+
+/// Deserializes a legacy account of exactly `space` bytes. Legacy accounts kept their type
+/// name, so they carry the discriminator of the current type.
+fn read_legacy_account<T: AnchorDeserialize>(
+    data: &[u8],
+    space: usize,
+    discriminator: &[u8],
+) -> Result<T> {
+    require!(
+        data.len() == space,
+        BtcLightClientError::UnsupportedStateVersion
+    );
+    require!(
+        data.starts_with(discriminator),
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+    Ok(T::try_from_slice(&data[discriminator.len()..])?)
+}
 
 #[account]
 pub struct BtcLightClientState {
+    /// Layout version, see `STATE_VERSION`. Kept first so every layout stores it at
+    /// `VERSION_OFFSET`.
+    pub version: u8,
     /// Latest verified block hash (stored in little-endian)
     pub latest_block_hash: [u8; 32],
     /// Height of the latest verified block
@@ -10,36 +224,360 @@ pub struct BtcLightClientState {
     pub latest_block_time: u32,
     /// Current difficulty target for the chain
     pub latest_period_target: [u8; 32],
-    /// Whether this client is for testnet or mainnet
-    pub is_testnet: bool,
+    /// Network whose consensus rules headers are checked against
+    pub network: Network,
     /// Required number of confirmations for tx verification
     pub min_confirmations: u64,
     /// Owner of the light client who can update settings
     pub owner: Pubkey,
+    /// Cumulative chainwork of the latest verified block (big-endian)
+    pub latest_chainwork: [u8; 32],
+    /// Timestamp of the first block in the current difficulty period
+    pub period_start_time: u32,
+    /// Timestamps of the latest block and its ten predecessors, oldest first
+    pub recent_block_times: [u32; MEDIAN_TIME_SPAN],
+    /// Whether anyone may submit headers, or only the owner
+    pub permissionless_relaying: bool,
+    /// Lamports paid from the reward vault for each header accepted into the chain
+    pub relayer_reward: u64,
+    /// Ring buffer holding recent headers, or the default key while headers live in
+    /// per-height `BlockHashEntry` accounts
+    pub header_store: Pubkey,
+    /// Number of blocks behind the tip whose entries are kept; older ones may be closed
+    /// (zero keeps all entries)
+    pub block_entry_retention: u64,
+    /// Signer allowed to close `TxVerifiedState` accounts once it has consumed them
+    pub tx_consumer: Pubkey,
+    /// Whether transaction proofs need a coinbase proof pinning the merkle tree depth. On by
+    /// default; without one the proof depth is unchecked.
+    pub require_coinbase_proof: bool,
+    /// Account proposed as the next owner, default while no transfer is pending
+    pub pending_owner: Pubkey,
+    /// Most blocks a branch may replace below the tip (zero leaves reorgs unbounded)
+    pub max_reorg_depth: u64,
+    /// Latest block pinned as final by the owner; no submission may replace it or any
+    /// block below it
+    pub checkpoint_height: u64,
+    /// Hash of the pinned block, the only header accepted at `checkpoint_height`
+    pub checkpoint_hash: [u8; 32],
+    /// Whether header submission is stopped
+    pub submission_paused: bool,
+    /// Whether transaction verification is stopped, including confirmation queries
+    pub verification_paused: bool,
 }
 
 #[account]
 pub struct BlockHashEntry {
     pub height: u64,
     pub hash: [u8; 32],
+    /// Cumulative chainwork up to and including this block (big-endian)
+    pub chainwork: [u8; 32],
+    /// Timestamps of this block and its ten predecessors, oldest first
+    pub recent_block_times: [u32; MEDIAN_TIME_SPAN],
+    /// Compact difficulty target of this block
+    pub bits: u32,
+    /// Target of the difficulty period this block is in (big-endian)
+    pub period_target: [u8; 32],
+    /// Timestamp of the first block in that period
+    pub period_start_time: u32,
+    /// Account that paid the rent and gets it back when the entry is closed
+    pub payer: Pubkey,
+}
+
+impl BlockHashEntry {
+    pub const SPACE: usize = 8 + // discriminator
+        8 + // height
+        32 + // hash
+        32 + // chainwork
+        4 * MEDIAN_TIME_SPAN + // recent_block_times
+        4 + // bits
+        32 + // period_target
+        4 + // period_start_time
+        32; // payer
 }
 
 impl BtcLightClientState {
     pub const SPACE: usize = 8 +  // discriminator
+        1 +  // version
         8 +  // latest_block_height
         32 +  // latest_block_hash
         4 +  // latest_block_time
         32 +  // latest_period_target
-        1 +  // is_testnet
+        1 +  // network
         8 +  // min_confirmations
-        32; // owner
+        32 +  // owner
+        32 +  // latest_chainwork
+        4 +  // period_start_time
+        4 * MEDIAN_TIME_SPAN + // recent_block_times
+        1 +  // permissionless_relaying
+        8 + // relayer_reward
+        32 + // header_store
+        8 + // block_entry_retention
+        32 + // tx_consumer
+        1 + // require_coinbase_proof
+        32 + // pending_owner
+        8 + // max_reorg_depth
+        8 + // checkpoint_height
+        32 + // checkpoint_hash
+        1 + // submission_paused
+        1; // verification_paused
+
+    /// Offset of `version` in the account data, right after the discriminator
+    pub const VERSION_OFFSET: usize = 8;
+
+    /// Layout version of state account data: 0 for the legacy layout, which predates the
+    /// version byte, otherwise the byte at `VERSION_OFFSET`
+    pub fn layout_version(data: &[u8]) -> Result<u8> {
+        require!(
+            data.starts_with(Self::DISCRIMINATOR),
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        if data.len() == LegacyBtcLightClientState::SPACE {
+            return Ok(0);
+        }
+        data.get(Self::VERSION_OFFSET)
+            .copied()
+            .ok_or_else(|| error!(BtcLightClientError::UnsupportedStateVersion))
+    }
+
+    /// Whether `submitter` may relay headers: anyone with permissionless relaying, otherwise
+    /// the owner and the allowlisted submitters
+    pub fn can_submit(&self, submitter: &Pubkey, allowlist: Option<&SubmitterAllowlist>) -> bool {
+        self.permissionless_relaying
+            || self.owner == *submitter
+            || allowlist.is_some_and(|allowlist| allowlist.submitters.contains(submitter))
+    }
+
+    /// Median time past of the latest block, the lower bound for the next block's timestamp
+    pub fn median_time_past(&self) -> u32 {
+        median_time_past(&self.recent_block_times)
+    }
 }
 
 #[account]
 pub struct TxVerifiedState {
     pub is_verified: bool,
+    /// Account that paid the rent and gets it back when the state is closed
+    pub payer: Pubkey,
+    /// Witness txid proven against the block's witness commitment, zeros if only the txid was
+    pub wtxid: [u8; 32],
+    /// Height and hash of the block the transaction was proven in
+    pub block_height: u64,
+    pub block_hash: [u8; 32],
+    /// Outputs whose value and script were checked, by ascending index
+    pub outputs: Vec<VerifiedOutput>,
+    /// Solana account the transaction names in an OP_RETURN output, if one was checked
+    pub recipient: Option<Pubkey>,
+    /// Memo following the recipient in that output
+    pub memo: Vec<u8>,
+    /// The block was final when last proven, so no reorg can orphan the record and its
+    /// confirmations no longer need the block's entry
+    pub is_final: bool,
 }
 
 impl TxVerifiedState {
-    pub const SPACE: usize = 8 + 1; // discriminator + is_verified
+    pub const SPACE: usize = 8 + // discriminator
+        1 + // is_verified
+        32 + // payer
+        32 + // wtxid
+        8 + // block_height
+        32 + // block_hash
+        4 + VerifiedOutput::SPACE * MAX_VERIFIED_OUTPUTS + // outputs
+        1 + 32 + // recipient
+        4 + MAX_RECIPIENT_MEMO_LEN + // memo
+        1; // is_final
+}
+
+/// Most outputs a single proof may check
+pub const MAX_VERIFIED_OUTPUTS: usize = 8;
+/// Longest memo fitting a standard 80-byte OP_RETURN payload after the recipient
+pub const MAX_RECIPIENT_MEMO_LEN: usize = 80 - 32;
+
+/// A transaction output proven by `verify_transaction`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct VerifiedOutput {
+    pub index: u32,
+    /// Value in satoshis
+    pub value: u64,
+    /// SHA-256 of the output's script_pubkey
+    pub script_hash: [u8; 32],
+}
+
+impl VerifiedOutput {
+    pub const SPACE: usize = 4 + 8 + 32; // index + value + script_hash
+}
+
+/// Proof that an outpoint was spent by a transaction in a verified block
+#[account]
+pub struct VerifiedSpend {
+    /// Transaction spending the outpoint
+    pub spending_tx_id: [u8; 32],
+    /// Input of that transaction which spends it
+    pub input_index: u32,
+    pub block_height: u64,
+    pub block_hash: [u8; 32],
+    pub sequence: u32,
+    pub lock_time: u32,
+}
+
+impl VerifiedSpend {
+    pub const SPACE: usize = 8 + // discriminator
+        32 + // spending_tx_id
+        4 + // input_index
+        8 + // block_height
+        32 + // block_hash
+        4 + // sequence
+        4; // lock_time
+}
+
+/// Most relayers the owner can allow besides itself
+pub const MAX_SUBMITTERS: usize = 16;
+
+/// Relayers the owner allows to submit headers while relaying is permissioned
+#[account]
+pub struct SubmitterAllowlist {
+    pub submitters: Vec<Pubkey>,
+}
+
+impl SubmitterAllowlist {
+    pub const SPACE: usize = 8 + 4 + 32 * MAX_SUBMITTERS; // discriminator + submitters
+}
+
+/// Block challenge of the signet the light client follows
+#[account]
+pub struct SignetConfig {
+    /// Script every block solution must satisfy
+    pub challenge: Vec<u8>,
+}
+
+impl SignetConfig {
+    pub fn space(challenge: &[u8]) -> usize {
+        8 + // discriminator
+        (4 + challenge.len()) // challenge (actual length)
+    }
+}
+
+/// Headers uploaded by a relayer over several transactions, applied at once by
+/// `finalize_header_buffer`. Every chunk is validated as it is appended, and the validated
+/// headers follow this struct in the account data as `StoredHeader` records.
+#[account(zero_copy)]
+pub struct HeaderBuffer {
+    /// Height of the first buffered header
+    pub start_height: u64,
+    /// Number of headers validated and buffered so far
+    pub count: u64,
+    /// Hash of the block the buffered headers build on, which must still be on the chain
+    /// when the buffer is finalized
+    pub parent_hash: [u8; 32],
+    /// Last buffered header, or the parent while the buffer is empty
+    pub tip: StoredHeader,
+}
+
+impl HeaderBuffer {
+    /// Offset of the first buffered `StoredHeader` record in the account data
+    pub const DATA_OFFSET: usize = 8 + std::mem::size_of::<HeaderBuffer>(); // discriminator + fields
+
+    pub fn space(header_count: usize) -> usize {
+        Self::DATA_OFFSET + std::mem::size_of::<StoredHeader>() * header_count
+    }
+}
+
+/// Raw transaction uploaded over several transactions, for transactions too large to be
+/// passed to `verify_transaction` directly. The raw bytes follow this struct in the account
+/// data, so verification hashes them in place.
+#[account(zero_copy)]
+pub struct TxBuffer {
+    /// Txid the uploader claims for the data, checked when the buffer is verified
+    pub tx_id: [u8; 32],
+}
+
+impl TxBuffer {
+    /// Offset of the raw transaction in the account data
+    pub const DATA_OFFSET: usize = 8 + std::mem::size_of::<TxBuffer>(); // discriminator + tx_id
+
+    pub fn space(data_len: usize) -> usize {
+        Self::DATA_OFFSET + data_len
+    }
+}
+
+/// Number of headers kept in the ring buffer, roughly a week of blocks
+pub const HEADER_STORE_CAPACITY: usize = 1024;
+
+/// A single ring buffer slot, laid out like `BlockHashEntry`
+#[zero_copy]
+pub struct StoredHeader {
+    pub height: u64,
+    pub hash: [u8; 32],
+    /// Cumulative chainwork up to and including this block (big-endian)
+    pub chainwork: [u8; 32],
+    /// Timestamps of this block and its ten predecessors, oldest first
+    pub recent_block_times: [u32; MEDIAN_TIME_SPAN],
+    /// Compact difficulty target of this block
+    pub bits: u32,
+    /// Timestamp of the first block in the difficulty period this block is in
+    pub period_start_time: u32,
+    /// Target of that period (big-endian)
+    pub period_target: [u8; 32],
+    pub _padding: [u8; 4],
+}
+
+/// Recent headers in a single account, the block at height `h` lives in slot
+/// `h % HEADER_STORE_CAPACITY`. A slot is only valid for heights up to the current tip;
+/// every reorg rewrites all slots from the fork point to the new tip.
+#[account(zero_copy)]
+pub struct HeaderStore {
+    /// Tip height when the store was attached; blocks up to it may still be read
+    /// from their `BlockHashEntry` accounts
+    pub base_height: u64,
+    pub headers: [StoredHeader; HEADER_STORE_CAPACITY],
+}
+
+impl HeaderStore {
+    pub const SPACE: usize = 8 + // discriminator
+        8 + // base_height
+        std::mem::size_of::<StoredHeader>() * HEADER_STORE_CAPACITY; // headers
+
+    /// Returns the header stored for `height`, unless its slot was reused or never written
+    pub fn get(&self, height: u64) -> Option<BlockHashEntry> {
+        let slot = &self.headers[height as usize % HEADER_STORE_CAPACITY];
+        (slot.height == height && slot.hash != [0; 32]).then(|| BlockHashEntry::from(slot))
+    }
+
+    /// Writes `entry` into its slot and returns the hash previously stored for its height
+    pub fn put(&mut self, entry: &BlockHashEntry) -> [u8; 32] {
+        let previous = self.get(entry.height).map_or([0; 32], |e| e.hash);
+        self.headers[entry.height as usize % HEADER_STORE_CAPACITY] = StoredHeader::from(entry);
+        previous
+    }
+}
+
+impl From<&BlockHashEntry> for StoredHeader {
+    fn from(entry: &BlockHashEntry) -> Self {
+        StoredHeader {
+            height: entry.height,
+            hash: entry.hash,
+            chainwork: entry.chainwork,
+            recent_block_times: entry.recent_block_times,
+            bits: entry.bits,
+            period_start_time: entry.period_start_time,
+            period_target: entry.period_target,
+            _padding: [0; 4],
+        }
+    }
+}
+
+impl From<&StoredHeader> for BlockHashEntry {
+    /// An entry with no rent payer recorded
+    fn from(header: &StoredHeader) -> Self {
+        BlockHashEntry {
+            height: header.height,
+            hash: header.hash,
+            chainwork: header.chainwork,
+            recent_block_times: header.recent_block_times,
+            bits: header.bits,
+            period_target: header.period_target,
+            period_start_time: header.period_start_time,
+            payer: Pubkey::default(),
+        }
+    }
 }
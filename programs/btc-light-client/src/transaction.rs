@@ -0,0 +1,373 @@
+use crate::{errors::BtcLightClientError, utils::sha256d};
+use anchor_lang::prelude::*;
+use bitcoin::Script;
+
+/// A transaction read in place from its serialization, so even one uploaded to a buffer
+/// account is hashed and checked straight from the account data without being decoded into
+/// the heap. Values and counts are little-endian.
+#[derive(Clone, Copy)]
+pub struct RawTransaction<'a> {
+    bytes: &'a [u8],
+    /// Inputs and outputs, the part both serializations share between version and witness
+    body: &'a [u8],
+    inputs: RawTxIns<'a>,
+    outputs: RawTxOuts<'a>,
+    lock_time: u32,
+}
+
+impl<'a> RawTransaction<'a> {
+    /// Parses a transaction with or without witness, rejecting trailing bytes like
+    /// `bitcoin::consensus::deserialize` does
+    pub fn parse(bytes: &'a [u8]) -> Result<Self> {
+        let mut reader = Reader::new(bytes);
+        reader.take(4)?; // version
+
+        // A zero input count is read as the segwit marker, which must be followed by flag 1
+        let segwit = reader.remaining().first() == Some(&0);
+        if segwit {
+            require!(
+                reader.take(2)? == [0, 1],
+                BtcLightClientError::InvalidTransactionFormat
+            );
+        }
+
+        let body_start = reader.position();
+        let inputs = RawTxIns::read(&mut reader)?;
+        let outputs = RawTxOuts::read(&mut reader)?;
+        let body = &bytes[body_start..reader.position()];
+
+        if segwit {
+            let mut has_witness = false;
+            for _ in 0..inputs.len() {
+                let items = reader.compact_size()?;
+                has_witness |= items > 0;
+                for _ in 0..items {
+                    reader.var_bytes()?;
+                }
+            }
+            // Setting the flag without any witness is not a valid serialization
+            require!(has_witness, BtcLightClientError::InvalidTransactionFormat);
+        }
+
+        let lock_time = reader.u32()?;
+        require!(
+            reader.remaining().is_empty(),
+            BtcLightClientError::InvalidTransactionFormat
+        );
+
+        Ok(RawTransaction {
+            bytes,
+            body,
+            inputs,
+            outputs,
+            lock_time,
+        })
+    }
+
+    /// Double SHA-256 of the serialization without witness, hashed from the slices around
+    /// the marker and witness rather than from a copy
+    pub fn txid(&self) -> [u8; 32] {
+        sha256d(&[
+            &self.bytes[..4],
+            self.body,
+            &self.bytes[self.bytes.len() - 4..],
+        ])
+    }
+
+    /// Double SHA-256 of the full serialization, equal to the txid without witness
+    pub fn wtxid(&self) -> [u8; 32] {
+        sha256d(&[self.bytes])
+    }
+
+    /// Size of the serialization without witness
+    pub fn base_size(&self) -> usize {
+        4 + self.body.len() + 4
+    }
+
+    pub fn inputs(&self) -> RawTxIns<'a> {
+        self.inputs
+    }
+
+    pub fn outputs(&self) -> RawTxOuts<'a> {
+        self.outputs
+    }
+
+    pub fn lock_time(&self) -> u32 {
+        self.lock_time
+    }
+}
+
+/// An input borrowed from a transaction serialization
+pub struct RawTxIn<'a> {
+    pub prev_txid: &'a [u8; 32],
+    pub prev_vout: u32,
+    pub script_sig: &'a [u8],
+    pub sequence: u32,
+}
+
+/// The inputs of a transaction, validated once and walked again on every access
+#[derive(Clone, Copy)]
+pub struct RawTxIns<'a> {
+    count: usize,
+    bytes: &'a [u8],
+}
+
+impl<'a> RawTxIns<'a> {
+    fn read(reader: &mut Reader<'a>) -> Result<Self> {
+        let count = reader.compact_size()?;
+        let start = reader.position();
+        for _ in 0..count {
+            read_input(reader)?;
+        }
+        Ok(RawTxIns {
+            count,
+            bytes: reader.since(start),
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<RawTxIn<'a>> {
+        self.iter().nth(index)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = RawTxIn<'a>> {
+        let mut reader = Reader::new(self.bytes);
+        (0..self.count).map_while(move |_| read_input(&mut reader).ok())
+    }
+}
+
+fn read_input<'a>(reader: &mut Reader<'a>) -> Result<RawTxIn<'a>> {
+    Ok(RawTxIn {
+        prev_txid: reader.take(32)?.try_into().unwrap(),
+        prev_vout: reader.u32()?,
+        script_sig: reader.var_bytes()?,
+        sequence: reader.u32()?,
+    })
+}
+
+/// An output borrowed from a transaction serialization
+pub struct RawTxOut<'a> {
+    /// Value in satoshis
+    pub value: u64,
+    pub script_pubkey: &'a Script,
+}
+
+/// The outputs of a transaction, validated once and walked again on every access
+#[derive(Clone, Copy)]
+pub struct RawTxOuts<'a> {
+    count: usize,
+    bytes: &'a [u8],
+}
+
+impl<'a> RawTxOuts<'a> {
+    fn read(reader: &mut Reader<'a>) -> Result<Self> {
+        let count = reader.compact_size()?;
+        let start = reader.position();
+        for _ in 0..count {
+            read_output(reader)?;
+        }
+        Ok(RawTxOuts {
+            count,
+            bytes: reader.since(start),
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<RawTxOut<'a>> {
+        self.iter().nth(index)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = RawTxOut<'a>> {
+        let mut reader = Reader::new(self.bytes);
+        (0..self.count).map_while(move |_| read_output(&mut reader).ok())
+    }
+}
+
+fn read_output<'a>(reader: &mut Reader<'a>) -> Result<RawTxOut<'a>> {
+    Ok(RawTxOut {
+        value: reader.u64()?,
+        script_pubkey: Script::from_bytes(reader.var_bytes()?),
+    })
+}
+
+/// Cursor over a serialization that fails instead of reading past its end
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Reader { bytes, position: 0 }
+    }
+
+    fn position(&self) -> usize {
+        self.position
+    }
+
+    fn remaining(&self) -> &'a [u8] {
+        &self.bytes[self.position..]
+    }
+
+    /// Bytes read since `start`
+    fn since(&self, start: usize) -> &'a [u8] {
+        &self.bytes[start..self.position]
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        require!(
+            len <= self.remaining().len(),
+            BtcLightClientError::InvalidTransactionFormat
+        );
+        let taken = &self.remaining()[..len];
+        self.position += len;
+        Ok(taken)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    /// Reads a CompactSize, which like in Bitcoin Core must use its shortest encoding
+    fn compact_size(&mut self) -> Result<usize> {
+        let (value, min) = match self.take(1)?[0] {
+            0xfd => (
+                u16::from_le_bytes(self.take(2)?.try_into().unwrap()) as u64,
+                0xfd,
+            ),
+            0xfe => (
+                u32::from_le_bytes(self.take(4)?.try_into().unwrap()) as u64,
+                0x1_0000,
+            ),
+            0xff => (self.u64()?, 0x1_0000_0000),
+            value => (value as u64, 0),
+        };
+        require!(value >= min, BtcLightClientError::InvalidTransactionFormat);
+        usize::try_from(value).map_err(|_| error!(BtcLightClientError::InvalidTransactionFormat))
+    }
+
+    fn var_bytes(&mut self) -> Result<&'a [u8]> {
+        let len = self.compact_size()?;
+        self.take(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::{consensus::encode::deserialize, hashes::Hash, Transaction};
+
+    // Two P2SH-P2WPKH inputs paying two P2SH outputs
+    const SEGWIT_TX: &str = "020000000001021fc20ba2bd745507b8e00679e3b362558f9457db374ca28ffa\
+        5243f4c23a4d5f00000000171600147c9dea14ffbcaec4b575e03f05ceb7a81cd3fcbffdffffff915d689\
+        be87b43337f42e26033df59807b768223368f189a023d0242d83776890000000017160014\
+        7c9dea14ffbcaec4b575e03f05ceb7a81cd3fcbffdffffff0200cdf5050000000017a9146803c72d9154\
+        a6a20f404bed6d3dcee07986235a8700e1f5050000000017a9144e6a4c7cb5b5562904843bdf816342f4\
+        db9f5797870247304402205e9bf6e70eb0e4b495bf483fd8e6e02da64900f290ef8aaa64bb32600d973c\
+        450220670896f5d0e5f33473e5f399ab680cc1d25c2d2afd15abd722f04978f28be887012103e4e4d931\
+        2b2261af508b367d8ba9be4f01b61d6d6e78bec499845b4f410bcf2702473044022045ac80596a6ac9c8\
+        c572f94708709adaf106677221122e08daf8b9741a04f66a022003ccd52a3b78f8fd08058fc04fc0cffa\
+        5f4c196c84eae9e37e2a85babe731b57012103e4e4d9312b2261af508b367d8ba9be4f01b61d6d6e78be\
+        c499845b4f410bcf276a000000";
+
+    fn hex(hex: &str) -> Vec<u8> {
+        let hex: String = hex.split_whitespace().collect();
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn assert_matches_bitcoin(bytes: &[u8]) {
+        let expected: Transaction = deserialize(bytes).unwrap();
+        let tx = RawTransaction::parse(bytes).unwrap();
+
+        assert_eq!(tx.txid(), expected.txid().to_byte_array());
+        assert_eq!(tx.wtxid(), expected.wtxid().to_byte_array());
+        assert_eq!(tx.base_size(), expected.base_size());
+        assert_eq!(tx.lock_time(), expected.lock_time.to_consensus_u32());
+        assert_eq!(tx.inputs().len(), expected.input.len());
+        for (input, expected) in tx.inputs().iter().zip(&expected.input) {
+            assert_eq!(
+                *input.prev_txid,
+                expected.previous_output.txid.to_byte_array()
+            );
+            assert_eq!(input.prev_vout, expected.previous_output.vout);
+            assert_eq!(input.script_sig, expected.script_sig.as_bytes());
+            assert_eq!(input.sequence, expected.sequence.to_consensus_u32());
+        }
+        assert_eq!(tx.outputs().len(), expected.output.len());
+        for (output, expected) in tx.outputs().iter().zip(&expected.output) {
+            assert_eq!(output.value, expected.value.to_sat());
+            assert_eq!(output.script_pubkey, expected.script_pubkey.as_script());
+        }
+    }
+
+    /// The witness transaction re-serialized without its witness
+    fn stripped(segwit: &[u8]) -> Vec<u8> {
+        let mut tx: Transaction = deserialize(segwit).unwrap();
+        tx.input.iter_mut().for_each(|input| input.witness.clear());
+        bitcoin::consensus::encode::serialize(&tx)
+    }
+
+    #[test]
+    fn parses_like_rust_bitcoin() {
+        let segwit = hex(SEGWIT_TX);
+        assert_matches_bitcoin(&segwit);
+        assert_matches_bitcoin(&stripped(&segwit));
+    }
+
+    #[test]
+    fn hashes_the_txid_without_the_witness() {
+        let segwit = hex(SEGWIT_TX);
+        let legacy = stripped(&segwit);
+        let tx = RawTransaction::parse(&segwit).unwrap();
+
+        assert_eq!(tx.txid(), sha256d(&[&legacy]));
+        assert_eq!(tx.base_size(), legacy.len());
+        assert_ne!(tx.wtxid(), tx.txid());
+    }
+
+    #[test]
+    fn rejects_malformed_serializations() {
+        let segwit = hex(SEGWIT_TX);
+        let legacy = stripped(&segwit);
+
+        // trailing and missing bytes
+        assert!(RawTransaction::parse(&[&legacy[..], &[0]].concat()).is_err());
+        assert!(RawTransaction::parse(&legacy[..legacy.len() - 1]).is_err());
+
+        // the segwit flag without any witness
+        let mut no_witness = legacy[..4].to_vec();
+        no_witness.extend_from_slice(&[0, 1]);
+        no_witness.extend_from_slice(&legacy[4..legacy.len() - 4]);
+        no_witness.extend_from_slice(&[0, 0]);
+        no_witness.extend_from_slice(&legacy[legacy.len() - 4..]);
+        assert!(RawTransaction::parse(&no_witness).is_err());
+
+        // an input count of 2 in three bytes instead of one
+        let mut non_minimal = legacy[..4].to_vec();
+        non_minimal.extend_from_slice(&[0xfd, 2, 0]);
+        non_minimal.extend_from_slice(&legacy[5..]);
+        assert!(deserialize::<Transaction>(&non_minimal).is_err());
+        assert!(RawTransaction::parse(&non_minimal).is_err());
+    }
+}
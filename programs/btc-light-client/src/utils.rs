@@ -1,59 +1,117 @@
 use crate::errors::BtcLightClientError;
-use crate::state::BlockHashEntry;
+use crate::state::{BlockHashEntry, OutputScript, MAX_RECIPIENT_MEMO_LEN};
 use anchor_lang::prelude::*;
-use bitcoin::hashes::{sha256d, Hash};
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::system_program::{transfer, Transfer};
+use bitcoin::consensus::encode::serialize;
+use bitcoin::opcodes::all::OP_RETURN;
+use bitcoin::pow::{CompactTarget, Target};
+use bitcoin::script::Instruction;
+use bitcoin::Transaction;
+
+/// Number of blocks between difficulty adjustments
+pub const DIFFICULTY_ADJUSTMENT_INTERVAL: u64 = 2016;
+/// Expected duration of a difficulty period in seconds (two weeks)
+pub const POW_TARGET_TIMESPAN: u32 = 14 * 24 * 60 * 60;
+/// Expected time between blocks in seconds (ten minutes)
+pub const POW_TARGET_SPACING: u32 = 10 * 60;
+/// How far the first block of a testnet4 period may predate its parent in seconds (BIP94)
+pub const MAX_TIMEWARP: u32 = 10 * 60;
+/// Number of blocks whose median timestamp a new block must exceed
+pub const MEDIAN_TIME_SPAN: usize = 11;
+/// How far a block timestamp may be ahead of the current time in seconds (two hours)
+pub const MAX_FUTURE_BLOCK_TIME: i64 = 2 * 60 * 60;
+/// Prefix of the coinbase output committing to the witness merkle root (BIP141)
+pub const WITNESS_COMMITMENT_HEADER: [u8; 6] = [0x6a, 0x24, 0xaa, 0x21, 0xa9, 0xed];
+/// Size of the header plus the 32-byte commitment
+pub const MIN_WITNESS_COMMITMENT_SIZE: usize = 38;
+/// Stripped transaction size that collides with an inner merkle node (CVE-2017-12842)
+pub const AMBIGUOUS_TX_SIZE: usize = 64;
+
+/// Double SHA-256 of the concatenated `data`, through the `sol_sha256` syscall
+pub fn sha256d(data: &[&[u8]]) -> [u8; 32] {
+    hashv(&[&hashv(data).to_bytes()]).to_bytes()
+}
+
+/// Txid of `tx`, hashed over its serialization without witness
+pub fn txid(tx: &Transaction) -> [u8; 32] {
+    sha256d(&[
+        &serialize(&tx.version),
+        &serialize(&tx.input),
+        &serialize(&tx.output),
+        &serialize(&tx.lock_time),
+    ])
+}
 
 pub fn verify_merkle_proof(
-    tx_hash: bitcoin::Txid,
-    merkle_root: bitcoin::TxMerkleNode,
+    tx_hash: [u8; 32],
+    merkle_root: &[u8; 32],
     tx_index: u32,
     proof: &[[u8; 32]],
 ) -> bool {
-    let mut current_hash = tx_hash.to_raw_hash();
+    index_fits_proof(tx_index, proof)
+        && merkle_root_from_proof(tx_hash, tx_index, proof) == *merkle_root
+}
 
-    if proof.is_empty() && tx_index != 0 {
-        return false;
-    }
+/// Whether `index` has no bits beyond the proof length, which folding would otherwise ignore
+pub fn index_fits_proof(index: u32, proof: &[[u8; 32]]) -> bool {
+    index.checked_shr(proof.len() as u32).unwrap_or(0) == 0
+}
+
+/// Folds the merkle `proof` of the leaf at `index` into the root of its tree
+pub fn merkle_root_from_proof(leaf: [u8; 32], index: u32, proof: &[[u8; 32]]) -> [u8; 32] {
+    let mut current_hash = leaf;
 
     for (i, next_hash) in proof.iter().enumerate() {
-        let mut concat = vec![];
-        // extracts the i-th bit of tx idx
-        if ((tx_index >> i) & 1) == 1 {
-            // If the bit is 1, the transaction is in the right subtree of the current hash
-            // Append the next hash and then the current hash to the concatenated hash value
-            concat.extend_from_slice(next_hash);
-            concat.extend_from_slice(&current_hash[..]);
+        // extracts the i-th bit of the index
+        current_hash = if ((index >> i) & 1) == 1 {
+            // If the bit is 1, the leaf is in the right subtree of the current hash
+            sha256d(&[next_hash, &current_hash])
         } else {
-            // If the bit is 0, the transaction is in the left subtree of the current hash
-            // Append the current hash and then the next hash to the concatenated hash value
-            concat.extend_from_slice(&current_hash[..]);
-            concat.extend_from_slice(next_hash);
-        }
-
-        current_hash = sha256d::Hash::hash(&concat);
+            // If the bit is 0, the leaf is in the left subtree of the current hash
+            sha256d(&[&current_hash, next_hash])
+        };
     }
 
-    &current_hash == &merkle_root.to_raw_hash()
+    current_hash
+}
+
+/// Index of the coinbase output carrying the witness commitment. Like Bitcoin Core, the last
+/// matching output counts.
+pub fn witness_commitment_index(coinbase: &Transaction) -> Option<usize> {
+    coinbase.output.iter().rposition(|output| {
+        let script = output.script_pubkey.as_bytes();
+        script.len() >= MIN_WITNESS_COMMITMENT_SIZE
+            && script.starts_with(&WITNESS_COMMITMENT_HEADER)
+    })
+}
+
+/// Whether `script` is exactly the script_pubkey `expected` describes
+pub fn verify_output_script(script: &bitcoin::Script, expected: &OutputScript) -> bool {
+    match expected {
+        OutputScript::Exact { script: bytes } => script.as_bytes() == bytes.as_slice(),
+        _ => script == expected.to_script_pubkey().as_script(),
+    }
 }
 
-pub fn verify_output_script(script: &bitcoin::Script, expected_hash: &[u8; 32]) -> bool {
-    if script.is_p2wsh() {
-        // P2WSH: Extract 32-byte hash directly
-        let script_bytes = script.as_bytes();
-        &script_bytes[2..34] == expected_hash
-    } else if script.is_p2sh() {
-        // P2SH: Extract 20-byte hash and pad to 32 bytes (right-padded with zeros)
-        let script_bytes = script.as_bytes();
-        let mut padded_hash = [0u8; 32];
-        padded_hash[..20].copy_from_slice(&script_bytes[2..22]);
-        &padded_hash == expected_hash
-    } else if script.is_p2tr() {
-        // P2TR: Extract 32-byte taproot output directly
-        let script_bytes = script.as_bytes();
-        &script_bytes[2..34] == expected_hash
-    } else {
-        false
+/// Reads a recipient commitment, `OP_RETURN <recipient pubkey || memo>`, from `script`
+pub fn parse_recipient_commitment(script: &bitcoin::Script) -> Option<(Pubkey, Vec<u8>)> {
+    let mut instructions = script.instructions();
+    match instructions.next()? {
+        Ok(Instruction::Op(OP_RETURN)) => {}
+        _ => return None,
+    }
+    let data = match instructions.next()? {
+        Ok(Instruction::PushBytes(data)) => data.as_bytes(),
+        _ => return None,
+    };
+    if instructions.next().is_some() || data.len() < 32 || data.len() > 32 + MAX_RECIPIENT_MEMO_LEN
+    {
+        return None;
     }
+
+    let (recipient, memo) = data.split_at(32);
+    Some((Pubkey::try_from(recipient).ok()?, memo.to_vec()))
 }
 
 pub fn mul_in_place(arr: &mut [u8; 32], multiplicator: u32) {
@@ -69,6 +127,79 @@ pub fn mul_in_place(arr: &mut [u8; 32], multiplicator: u32) {
     }
 }
 
+pub fn div_in_place(arr: &mut [u8; 32], divisor: u32) {
+    let casted_div: u64 = divisor as u64;
+    let mut remainder: u64 = 0;
+
+    for byte in arr.iter_mut() {
+        let val = (remainder << 8) | (*byte as u64);
+        *byte = (val / casted_div) as u8;
+        remainder = val % casted_div;
+    }
+}
+
+/// Computes the `bits` required at a retarget block, following Bitcoin Core's
+/// `CalculateNextWorkRequired`: the previous period target is scaled by the time the
+/// period actually took, clamped to a factor of four, and capped at the network's pow limit.
+pub fn calculate_next_work_required(
+    period_target: &[u8; 32],
+    period_start_time: u32,
+    last_block_time: u32,
+    pow_limit: Target,
+) -> CompactTarget {
+    let actual_timespan = (last_block_time as i64 - period_start_time as i64).clamp(
+        (POW_TARGET_TIMESPAN / 4) as i64,
+        (POW_TARGET_TIMESPAN * 4) as i64,
+    );
+
+    let mut new_target = *period_target;
+    mul_in_place(&mut new_target, actual_timespan as u32);
+    div_in_place(&mut new_target, POW_TARGET_TIMESPAN);
+
+    Target::from_be_bytes(new_target)
+        .min(pow_limit)
+        .to_compact_lossy()
+}
+
+pub fn median_time_past(block_times: &[u32; MEDIAN_TIME_SPAN]) -> u32 {
+    let mut sorted = *block_times;
+    sorted.sort_unstable();
+    sorted[MEDIAN_TIME_SPAN / 2]
+}
+
+/// Drops the oldest timestamp and appends `block_time` as the newest
+pub fn push_block_time(block_times: &mut [u32; MEDIAN_TIME_SPAN], block_time: u32) {
+    block_times.rotate_left(1);
+    block_times[MEDIAN_TIME_SPAN - 1] = block_time;
+}
+
+/// Grows `account` to `new_len` bytes, topping its rent up from `payer`. The added bytes
+/// start zeroed.
+pub fn realloc_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    new_len: usize,
+) -> Result<()> {
+    let shortfall = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(account.lamports());
+    if shortfall > 0 {
+        transfer(
+            CpiContext::new(
+                system_program.clone(),
+                Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    account.realloc(new_len, true)?;
+    Ok(())
+}
+
 fn get_block_hash_pda(height: u64, program_id: &Pubkey) -> (Pubkey, u8) {
     Pubkey::find_program_address(&[b"block_hash_entry", &height.to_le_bytes()], program_id)
 }
@@ -92,3 +223,94 @@ pub fn get_and_verify_block_hash_account<'info>(
     BlockHashEntry::try_deserialize(&mut &account_info.data.borrow()[..])
         .map_err(|_| error!(BtcLightClientError::DeserializationError))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target_bytes(bits: u32) -> [u8; 32] {
+        Target::from_compact(CompactTarget::from_consensus(bits)).to_be_bytes()
+    }
+
+    fn next_bits(period_bits: u32, timespan: u32, pow_limit: Target) -> u32 {
+        let period_start_time = 1_640_422_999;
+        calculate_next_work_required(
+            &target_bytes(period_bits),
+            period_start_time,
+            period_start_time + timespan,
+            pow_limit,
+        )
+        .to_consensus()
+    }
+
+    #[test]
+    fn retargets_mainnet_block_717696() {
+        // the period started with block #715680 and ended with block #717695
+        let bits = calculate_next_work_required(
+            &target_bytes(0x170b98ab),
+            1_640_422_999,
+            1_641_627_659,
+            Target::MAX_ATTAINABLE_MAINNET,
+        );
+        assert_eq!(bits.to_consensus(), 0x170b8c8b);
+    }
+
+    #[test]
+    fn keeps_target_for_exact_timespan() {
+        let bits = next_bits(
+            0x170b98ab,
+            POW_TARGET_TIMESPAN,
+            Target::MAX_ATTAINABLE_MAINNET,
+        );
+        assert_eq!(bits, 0x170b98ab);
+    }
+
+    #[test]
+    fn clamps_adjustment_to_a_factor_of_four() {
+        let pow_limit = Target::MAX_ATTAINABLE_MAINNET;
+        let slowest = next_bits(0x170b98ab, POW_TARGET_TIMESPAN * 4, pow_limit);
+        assert_eq!(slowest, 0x172e62ac);
+        assert_eq!(
+            next_bits(0x170b98ab, POW_TARGET_TIMESPAN * 10, pow_limit),
+            slowest
+        );
+
+        let fastest = next_bits(0x170b98ab, POW_TARGET_TIMESPAN / 4, pow_limit);
+        assert_eq!(fastest, 0x1702e62a);
+        assert_eq!(next_bits(0x170b98ab, 0, pow_limit), fastest);
+    }
+
+    #[test]
+    fn caps_target_at_pow_limit() {
+        let bits = next_bits(
+            0x1d00ffff,
+            POW_TARGET_TIMESPAN * 4,
+            Target::MAX_ATTAINABLE_MAINNET,
+        );
+        assert_eq!(bits, 0x1d00ffff);
+    }
+
+    #[test]
+    fn index_must_fit_the_proof() {
+        let proof = [[0u8; 32]; 2];
+        assert!(index_fits_proof(3, &proof));
+        assert!(!index_fits_proof(4, &proof));
+        assert!(index_fits_proof(0, &[]));
+        assert!(!index_fits_proof(1, &[]));
+        assert!(index_fits_proof(u32::MAX, &[[0u8; 32]; 32]));
+    }
+
+    #[test]
+    fn median_time_past_ignores_order() {
+        let times = [5, 1, 9, 3, 7, 11, 2, 10, 4, 8, 6];
+        assert_eq!(median_time_past(&times), 6);
+    }
+
+    #[test]
+    fn push_block_time_drops_the_oldest() {
+        let mut times = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+        push_block_time(&mut times, 100);
+        assert_eq!(times, [2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 100]);
+        assert_eq!(median_time_past(&times), 7);
+    }
+}
@@ -0,0 +1,219 @@
+import * as anchor from "@coral-xyz/anchor";
+import { BitvmBridge } from "../target/types/bitvm_bridge";
+import { expect } from "chai";
+import { describe, it, before } from "node:test";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { BankrunProvider } from "anchor-bankrun";
+import { startAnchor } from "solana-bankrun";
+import { createHash } from "crypto";
+import { MINT_SIZE, MintLayout, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+
+const IDL = require("../target/idl/bitvm_bridge.json");
+const PROGRAM_ID = new PublicKey(IDL.address);
+
+// Migrates a bridge state written before the deposit script hash was added, whose token
+// was minted by the owner
+describe("Bitvm Bridge Migration Tests", async () => {
+  const context = await startAnchor(
+    "",
+    [{ name: "bitvm_bridge", programId: PROGRAM_ID }],
+    []
+  );
+  const provider = new BankrunProvider(context);
+
+  const program = new anchor.Program<BitvmBridge>(IDL, provider);
+
+  const [bridgeStatePda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("bridge_state")],
+    program.programId
+  );
+
+  const mintAccount = Keypair.generate().publicKey;
+  const legacyParams = {
+    maxBtcPerMint: 1000000,
+    minBtcPerMint: 1000,
+    maxBtcPerBurn: 1000000,
+    minBtcPerBurn: 1000,
+    skipTxVerification: false,
+    burnPaused: true,
+    maxFeeRate: 50,
+    lpWithdrawTimeout: 3600,
+  };
+
+  before(async () => {
+    // The legacy state: owner, mint, limits, flags, max fee rate and LP withdraw timeout,
+    // in an account sized 8 + size_of::<BridgeState>() of the legacy struct
+    const u64 = (value: number) => {
+      const buf = Buffer.alloc(8);
+      buf.writeBigUInt64LE(BigInt(value));
+      return buf;
+    };
+    const data = Buffer.alloc(128);
+    Buffer.concat([
+      discriminator("BridgeState"),
+      provider.wallet.publicKey.toBuffer(),
+      mintAccount.toBuffer(),
+      u64(legacyParams.maxBtcPerMint),
+      u64(legacyParams.minBtcPerMint),
+      u64(legacyParams.maxBtcPerBurn),
+      u64(legacyParams.minBtcPerBurn),
+      Buffer.from([0, 1]),
+      u64(legacyParams.maxFeeRate),
+      u64(legacyParams.lpWithdrawTimeout),
+    ]).copy(data);
+
+    const rent = await context.banksClient.getRent();
+    context.setAccount(bridgeStatePda, {
+      lamports: Number(rent.minimumBalance(BigInt(data.length))),
+      data,
+      owner: program.programId,
+      executable: false,
+    });
+
+    // The legacy token, with the owner as mint and freeze authority
+    const mintData = Buffer.alloc(MINT_SIZE);
+    MintLayout.encode(
+      {
+        mintAuthorityOption: 1,
+        mintAuthority: provider.wallet.publicKey,
+        supply: BigInt(0),
+        decimals: 8,
+        isInitialized: true,
+        freezeAuthorityOption: 1,
+        freezeAuthority: provider.wallet.publicKey,
+      },
+      mintData
+    );
+    context.setAccount(mintAccount, {
+      lamports: Number(rent.minimumBalance(BigInt(MINT_SIZE))),
+      data: mintData,
+      owner: TOKEN_PROGRAM_ID,
+      executable: false,
+    });
+  });
+
+  it("Migrate the legacy bridge state", async () => {
+    // the legacy account is too short for the current layout
+    const legacyAccount = await context.banksClient.getAccount(bridgeStatePda);
+    expect(legacyAccount.data.length).to.equal(128);
+
+    // only the owner recorded in the legacy state can migrate it
+    const stranger = Keypair.generate();
+    fund(stranger.publicKey);
+    try {
+      await program.methods
+        .migrateBridgeState()
+        .accounts({ owner: stranger.publicKey })
+        .signers([stranger])
+        .rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("Unauthorized owner");
+    }
+
+    await program.methods.migrateBridgeState().accounts({}).rpc();
+
+    const state = await program.account.bridgeState.fetch(bridgeStatePda);
+    expect(state.owner).to.deep.equal(provider.wallet.publicKey);
+    expect(state.mintAccount).to.deep.equal(mintAccount);
+    expect(state.maxBtcPerMint.toNumber()).to.equal(legacyParams.maxBtcPerMint);
+    expect(state.minBtcPerMint.toNumber()).to.equal(legacyParams.minBtcPerMint);
+    expect(state.maxBtcPerBurn.toNumber()).to.equal(legacyParams.maxBtcPerBurn);
+    expect(state.minBtcPerBurn.toNumber()).to.equal(legacyParams.minBtcPerBurn);
+    expect(state.skipTxVerification).to.be.false;
+    expect(state.burnPaused).to.be.true;
+    expect(state.maxFeeRate.toNumber()).to.equal(legacyParams.maxFeeRate);
+    expect(state.lpWithdrawTimeout.toNumber()).to.equal(
+      legacyParams.lpWithdrawTimeout
+    );
+    // deposits are refused until the owner sets the deposit script
+    expect(state.depositScriptHash).to.deep.equal(Array(32).fill(0));
+
+    // the account now has the current size and is rent exempt for it
+    const account = await context.banksClient.getAccount(bridgeStatePda);
+    const rent = await context.banksClient.getRent();
+    expect(account.data.length).to.equal(160);
+    expect(BigInt(account.lamports)).to.equal(
+      rent.minimumBalance(BigInt(account.data.length))
+    );
+
+    try {
+      await program.methods.migrateBridgeState().accounts({}).rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include(
+        "Bridge state already has the current layout"
+      );
+    }
+  });
+
+  it("Use the migrated bridge state", async () => {
+    const depositScriptHash = createHash("sha256")
+      .update(Buffer.from("0020" + "11".repeat(32), "hex"))
+      .digest();
+    await program.methods
+      .setDepositScriptHash(Array.from(depositScriptHash))
+      .accounts({})
+      .rpc();
+
+    const state = await program.account.bridgeState.fetch(bridgeStatePda);
+    expect(Buffer.from(state.depositScriptHash)).to.deep.equal(
+      depositScriptHash
+    );
+  });
+
+  it("Hand the mint authority to the bridge state", async () => {
+    const stranger = Keypair.generate();
+    fund(stranger.publicKey);
+    try {
+      await program.methods
+        .handOverMintAuthority()
+        .accounts({ owner: stranger.publicKey, mintAccount })
+        .signers([stranger])
+        .rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("Unauthorized owner");
+    }
+
+    await program.methods
+      .handOverMintAuthority()
+      .accounts({ mintAccount })
+      .rpc();
+
+    const account = await context.banksClient.getAccount(mintAccount);
+    const mint = MintLayout.decode(Buffer.from(account.data));
+    expect(mint.mintAuthorityOption).to.equal(1);
+    expect(mint.mintAuthority).to.deep.equal(bridgeStatePda);
+    // the owner keeps the freeze authority
+    expect(mint.freezeAuthority).to.deep.equal(provider.wallet.publicKey);
+
+    // the owner can no longer mint, nor hand over again
+    try {
+      await program.methods
+      .handOverMintAuthority()
+      .accounts({ mintAccount })
+      .rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).not.to.include("should have thrown error");
+    }
+  });
+
+  function fund(address: PublicKey) {
+    context.setAccount(address, {
+      lamports: 1_000_000_000,
+      data: Buffer.alloc(0),
+      owner: anchor.web3.SystemProgram.programId,
+      executable: false,
+    });
+  }
+});
+
+// Anchor account discriminator of the account type `name`
+function discriminator(name: string): Buffer {
+  return createHash("sha256")
+    .update(`account:${name}`)
+    .digest()
+    .subarray(0, 8);
+}
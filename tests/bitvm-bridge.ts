@@ -6,7 +6,9 @@ import { describe, it } from "node:test";
 import { PublicKey, Keypair, Connection } from "@solana/web3.js";
 import { BankrunProvider } from "anchor-bankrun";
 import { startAnchor } from "solana-bankrun";
+import { createHash } from "crypto";
 import {
+  createAssociatedTokenAccountIdempotentInstruction,
   getAssociatedTokenAddressSync,
   unpackAccount,
 } from "@solana/spl-token";
@@ -82,6 +84,15 @@ describe("Test Bitvm Bridge", async () => {
     bitvmBridgeProgram.programId
   );
 
+  // Entry of the block the deposit is verified in
+  const [blockHashEntryPda] = PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("block_hash_entry"),
+      new anchor.BN(230627).toArrayLike(Buffer, "le", 8),
+    ],
+    btcLightClientProgram.programId
+  );
+
   it("Initialize BTC Light Client", async () => {
     console.log("Starting BTC Light Client initialization...");
     const genesisBlock = {
@@ -91,24 +102,39 @@ describe("Test Bitvm Bridge", async () => {
         "hex"
       ),
       time: 1736757020,
+      // timestamps of the ten preceding blocks, unknown here
+      prevBlockTimes: Array(10).fill(0),
       target: Buffer.from(
         "0003400100000000000000000000000000000000000000000000000000000000",
         "hex"
       ),
+      chainwork: Buffer.alloc(32),
+      periodStartTime: 1736757020,
     };
 
     await btcLightClientProgram.methods
       .initialize(
         new anchor.BN(genesisBlock.height),
         Array.from(genesisBlock.hash),
-        genesisBlock.time,
         Array.from(genesisBlock.target),
-        false,
+        {
+          blockTime: genesisBlock.time,
+          prevBlockTimes: genesisBlock.prevBlockTimes,
+          chainwork: Array.from(genesisBlock.chainwork),
+          periodStartTime: genesisBlock.periodStartTime,
+        },
+        { signet: {} },
         new anchor.BN(0)
       )
       .accounts({})
       .rpc();
 
+    // The signet fixtures come without the block's coinbase
+    await btcLightClientProgram.methods
+      .updateRequireCoinbaseProof(false)
+      .accounts({})
+      .rpc();
+
     const state = await btcLightClientProgram.account.btcLightClientState.fetch(
       statePda
     );
@@ -122,7 +148,7 @@ describe("Test Bitvm Bridge", async () => {
     expect(Buffer.from(state.latestPeriodTarget).toString("hex")).to.equal(
       Buffer.from(genesisBlock.target).toString("hex")
     );
-    expect(state.isTestnet).to.be.false;
+    expect(state.network).to.deep.equal({ signet: {} });
     console.log("BTC Light Client initialization complete");
   });
 
@@ -199,11 +225,28 @@ describe("Test Bitvm Bridge", async () => {
         txIndex: txIndex,
         merkleProof: txMerkleProof.map((proof) => Array.from(proof)),
         rawTx: rawTx,
-        outputIndex: outputIndex,
-        expectedAmount: new anchor.BN(expectedAmount),
-        expectedScriptHash: Array.from(expectedScriptHash),
+        expectedOutputs: [
+          {
+            index: outputIndex,
+            minAmount: new anchor.BN(expectedAmount),
+            script: {
+              p2wsh: { scriptHash: Array.from(expectedScriptHash) },
+            },
+          },
+        ],
+        recipientOutput: null,
+        coinbaseProof: null,
+      })
+      .accountsPartial({
+        blockHashEntry: PublicKey.findProgramAddressSync(
+          [
+            Buffer.from("block_hash_entry"),
+            new anchor.BN(blockHeight).toArrayLike(Buffer, "le", 8),
+          ],
+          btcLightClientProgram.programId
+        )[0],
+        headerStore: null,
       })
-      .accounts({})
       .preInstructions([
         anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({
           units: 1_000_000,
@@ -264,9 +307,66 @@ describe("Test Bitvm Bridge", async () => {
     );
     assert.isTrue(txState.isVerified);
 
-    // Amount of tokens to mint.
+    // Let the bridge close verified tx states once it has consumed them
+    await btcLightClientProgram.methods
+      .updateTxConsumer(bridgeStatePda)
+      .accounts({})
+      .rpc();
+
+    // Deposits pay to the P2WSH output proven above
+    const depositScript = Buffer.from(
+      "002085f1940c71a1e1a852db646fa0f79cf1e5defc9e4bda671ad4cf9000ada74b41",
+      "hex"
+    );
+    await bitvmBridgeProgram.methods
+      .setDepositScriptHash(
+        Array.from(createHash("sha256").update(depositScript).digest())
+      )
+      .accounts({ owner: owner.publicKey })
+      .rpc();
+
+    // Minting more than the proven deposit output carries is rejected
+    try {
+      await bitvmBridgeProgram.methods
+        .mint(Array.from(txId), new anchor.BN(200000))
+        .accountsPartial({
+          mintAuthority: owner.publicKey,
+          recipient: owner.publicKey,
+          mintAccount: mintKeypair.publicKey,
+          txVerifiedStatePayer: owner.publicKey,
+          blockHashEntry: blockHashEntryPda,
+          headerStore: null,
+        })
+        .rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("Mismatch btc amount");
+    }
+
+    // The deposit commits to no recipient, so only the operator can mint it
+    try {
+      await bitvmBridgeProgram.methods
+        .mintToCommittedRecipient(Array.from(txId))
+        .accountsPartial({
+          payer: owner.publicKey,
+          recipient: owner.publicKey,
+          mintAccount: mintKeypair.publicKey,
+          txVerifiedStatePayer: owner.publicKey,
+          blockHashEntry: blockHashEntryPda,
+          headerStore: null,
+        })
+        .rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include(
+        "Recipient does not match the commitment"
+      );
+    }
+
+    // Amount of tokens to mint, equal to the proven output value.
     const amount = new anchor.BN(100000);
-    // Mint the tokens to the associated token account.
+    // Mint the tokens to the associated token account. The deposit was proven in the
+    // checkpoint block, which is final, so minting needs no entry to check it is canonical.
 
     await bitvmBridgeProgram.methods
       .mint(Array.from(txId), amount)
@@ -274,6 +374,9 @@ describe("Test Bitvm Bridge", async () => {
         mintAuthority: owner.publicKey,
         recipient: owner.publicKey,
         mintAccount: mintKeypair.publicKey,
+        txVerifiedStatePayer: owner.publicKey,
+        blockHashEntry: null,
+        headerStore: null,
       })
       .rpc();
 
@@ -282,6 +385,117 @@ describe("Test Bitvm Bridge", async () => {
     );
 
     assert.isTrue(newbtcTxState.isMinted);
+
+    // The consumed verification is closed and its rent refunded
+    assert.isNull(
+      await provider.connection.getAccountInfo(txVerifiedStatePda)
+    );
+  });
+
+  it("Owner mints only to the committed recipient", async () => {
+    // A deposit to the same P2WSH output that commits to a recipient, as the light client
+    // records it once verified
+    const committedTxId = Buffer.alloc(32, 0x7c);
+    const committedRecipient = new Keypair();
+    const [committedStatePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("tx_verified_state"), committedTxId],
+      btcLightClientProgram.programId
+    );
+    const entry = await btcLightClientProgram.account.blockHashEntry.fetch(
+      blockHashEntryPda
+    );
+    const data = await btcLightClientProgram.coder.accounts.encode(
+      "TxVerifiedState",
+      {
+        isVerified: true,
+        payer: owner.publicKey,
+        wtxid: Array(32).fill(0),
+        blockHeight: new anchor.BN(230627),
+        blockHash: entry.hash,
+        outputs: [
+          {
+            index: 0,
+            value: new anchor.BN(100000),
+            scriptHash: Array.from(
+              Buffer.from(
+                "85f1940c71a1e1a852db646fa0f79cf1e5defc9e4bda671ad4cf9000ada74b41",
+                "hex"
+              )
+            ),
+          },
+        ],
+        recipient: committedRecipient.publicKey,
+        memo: Buffer.alloc(0),
+        isFinal: false,
+      }
+    );
+    const rent = await context.banksClient.getRent();
+    context.setAccount(committedStatePda, {
+      lamports: Number(rent.minimumBalance(BigInt(data.length))),
+      data,
+      owner: btcLightClientProgram.programId,
+      executable: false,
+    });
+
+    // the owner cannot redirect a deposit that names its recipient
+    try {
+      await bitvmBridgeProgram.methods
+        .mint(Array.from(committedTxId), new anchor.BN(100000))
+        .accountsPartial({
+          mintAuthority: owner.publicKey,
+          recipient: owner.publicKey,
+          mintAccount: mintKeypair.publicKey,
+          txVerifiedStatePayer: owner.publicKey,
+          blockHashEntry: blockHashEntryPda,
+          headerStore: null,
+        })
+        .rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include(
+        "Recipient does not match the commitment"
+      );
+    }
+
+    // a block not known to be final is checked against its entry
+    try {
+      await bitvmBridgeProgram.methods
+        .mint(Array.from(committedTxId), new anchor.BN(100000))
+        .accountsPartial({
+          mintAuthority: owner.publicKey,
+          recipient: committedRecipient.publicKey,
+          mintAccount: mintKeypair.publicKey,
+          txVerifiedStatePayer: owner.publicKey,
+          blockHashEntry: null,
+          headerStore: null,
+        })
+        .rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("Block not stored");
+    }
+
+    await bitvmBridgeProgram.methods
+      .mint(Array.from(committedTxId), new anchor.BN(100000))
+      .accountsPartial({
+        mintAuthority: owner.publicKey,
+        recipient: committedRecipient.publicKey,
+        mintAccount: mintKeypair.publicKey,
+        txVerifiedStatePayer: owner.publicKey,
+        blockHashEntry: blockHashEntryPda,
+        headerStore: null,
+      })
+      .rpc();
+
+    const tokenAccountAddress = getAssociatedTokenAddressSync(
+      mintKeypair.publicKey,
+      committedRecipient.publicKey
+    );
+    const tokenAccount = await unpackAccount(
+      tokenAccountAddress,
+      await provider.connection.getAccountInfo(tokenAccountAddress)
+    );
+    assert.equal(tokenAccount.amount.toString(), "100000");
   });
 
   it("Burn some tokens from your wallet!", async () => {
@@ -355,6 +569,8 @@ describe("Test Bitvm Bridge", async () => {
           mintAuthority: nonOwner.publicKey,
           recipient: recipient.publicKey,
           mintAccount: mintKeypair.publicKey,
+          txVerifiedState: null,
+          txVerifiedStatePayer: null,
         })
         .signers([nonOwner])
         .rpc();
@@ -374,6 +590,8 @@ describe("Test Bitvm Bridge", async () => {
           mintAuthority: owner.publicKey,
           recipient: recipient.publicKey,
           mintAccount: mintKeypair.publicKey,
+          txVerifiedState: null,
+          txVerifiedStatePayer: null,
         })
         .rpc();
       assert.fail("should fail");
@@ -509,6 +727,10 @@ describe("Test Bitvm Bridge", async () => {
             mintAuthority: owner.publicKey,
             recipient: owner.publicKey,
             mintAccount: mintKeypair.publicKey,
+            txVerifiedState: null,
+            txVerifiedStatePayer: null,
+            blockHashEntry: null,
+            headerStore: null,
           })
           .rpc();
         assert.fail("should fail");
@@ -528,6 +750,10 @@ describe("Test Bitvm Bridge", async () => {
             mintAuthority: owner.publicKey,
             recipient: owner.publicKey,
             mintAccount: mintKeypair.publicKey,
+            txVerifiedState: null,
+            txVerifiedStatePayer: null,
+            blockHashEntry: null,
+            headerStore: null,
           })
           .rpc();
         assert.fail("should fail");
@@ -625,6 +851,99 @@ describe("Test Bitvm Bridge", async () => {
         .accounts({})
         .rpc();
     });
+
+    it("One Bitcoin payment backs only one LP withdraw claim", async () => {
+      // Verification is skipped since the admin tests, so claims rest on the txid alone
+      const contractTokenAccount = getAssociatedTokenAddressSync(
+        mintKeypair.publicKey,
+        bridgeStatePda,
+        true
+      );
+      const lpTokenAccount = getAssociatedTokenAddressSync(
+        mintKeypair.publicKey,
+        lpKeypair.publicKey
+      );
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          createAssociatedTokenAccountIdempotentInstruction(
+            owner.publicKey,
+            contractTokenAccount,
+            bridgeStatePda,
+            mintKeypair.publicKey
+          ),
+          createAssociatedTokenAccountIdempotentInstruction(
+            owner.publicKey,
+            lpTokenAccount,
+            lpKeypair.publicKey,
+            mintKeypair.publicKey
+          )
+        )
+      );
+
+      const [lpStatePda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("lp_state"), lpId.toArrayLike(Buffer, "le", 8)],
+        bitvmBridgeProgram.programId
+      );
+      const withdrawIds = [new anchor.BN(1), new anchor.BN(2)];
+      for (const withdrawId of withdrawIds) {
+        await bitvmBridgeProgram.methods
+          .withdrawByLp(
+            withdrawId,
+            "bc1qreceiver",
+            Array(32).fill(7),
+            new anchor.BN(9000),
+            lpId,
+            new anchor.BN(10000),
+            new anchor.BN(10)
+          )
+          .accountsPartial({
+            userTokenAccount: getAssociatedTokenAddressSync(
+              mintKeypair.publicKey,
+              owner.publicKey
+            ),
+            contractTokenAccount,
+          })
+          .rpc();
+      }
+
+      const btcTxId = Buffer.alloc(32, 0x3b);
+      const claim = (withdrawId: anchor.BN) =>
+        bitvmBridgeProgram.methods
+          .claimLpWithdraw(withdrawId, Array.from(btcTxId), new anchor.BN(9500))
+          .accountsPartial({
+            lpState: lpStatePda,
+            contractTokenAccount,
+            lpTokenAccount,
+            txVerifiedState: null,
+            txVerifiedStatePayer: null,
+            blockHashEntry: null,
+            headerStore: null,
+          });
+
+      await claim(withdrawIds[0]).rpc();
+      const [txClaimedStatePda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("tx_claimed_state"), btcTxId],
+        bitvmBridgeProgram.programId
+      );
+      const txClaimedState =
+        await bitvmBridgeProgram.account.txClaimedState.fetch(
+          txClaimedStatePda
+        );
+      expect(txClaimedState.isClaimed).to.be.true;
+
+      try {
+        await claim(withdrawIds[1]).rpc();
+        assert.fail("should fail");
+      } catch (error) {
+        assert.include(error.message, "TxAlreadyClaimed");
+      }
+
+      const lpAccount = await unpackAccount(
+        lpTokenAccount,
+        await provider.connection.getAccountInfo(lpTokenAccount)
+      );
+      assert.equal(lpAccount.amount.toString(), "10000");
+    });
   });
 
   describe("Fee Management", () => {
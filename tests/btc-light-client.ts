@@ -4,6 +4,9 @@ import { BtcLightClient } from "../target/types/btc_light_client";
 import { expect } from "chai";
 import { PublicKey } from "@solana/web3.js";
 
+// Ceiling on the compute units spent per submitted header, `yarn bench` prints the cost
+const SUBMIT_HEADER_CU_LIMIT = 40_000;
+
 describe("BTC Light Client Tests", () => {
   const provider = anchor.AnchorProvider.env();
   anchor.setProvider(provider);
@@ -17,10 +20,15 @@ describe("BTC Light Client Tests", () => {
       "hex"
     ),
     time: 1641627092,
+    // timestamps of the ten preceding blocks, unknown here
+    prevBlockTimes: Array(10).fill(0),
     target: Buffer.from(
       "0000000000000000000B98AB0000000000000000000000000000000000000000",
       "hex"
     ),
+    chainwork: Buffer.alloc(32),
+    // time of block #715680, the first block of the difficulty period
+    periodStartTime: 1640422999,
   };
 
   // Block #717695 header data,
@@ -45,7 +53,10 @@ describe("BTC Light Client Tests", () => {
     "hex"
   );
 
+  const relayerReward = 5000;
+
   let btcLightClientState: PublicKey;
+  let rewardVault: PublicKey;
   before(async () => {
     // Get state PDA
     const [statePda] = PublicKey.findProgramAddressSync(
@@ -53,6 +64,11 @@ describe("BTC Light Client Tests", () => {
       program.programId
     );
     btcLightClientState = statePda;
+
+    [rewardVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("relayer_reward_vault")],
+      program.programId
+    );
   });
 
   it("Initialize state", async () => {
@@ -60,9 +76,14 @@ describe("BTC Light Client Tests", () => {
       .initialize(
         new anchor.BN(genesisBlock.height),
         Array.from(genesisBlock.hash),
-        genesisBlock.time,
         Array.from(genesisBlock.target),
-        false,
+        {
+          blockTime: genesisBlock.time,
+          prevBlockTimes: genesisBlock.prevBlockTimes,
+          chainwork: Array.from(genesisBlock.chainwork),
+          periodStartTime: genesisBlock.periodStartTime,
+        },
+        { mainnet: {} },
         new anchor.BN(0)
       )
       .accounts({})
@@ -82,7 +103,68 @@ describe("BTC Light Client Tests", () => {
     expect(Buffer.from(state.latestPeriodTarget).toString("hex")).to.equal(
       genesisBlock.target.toString("hex")
     );
-    expect(state.isTestnet).to.be.false;
+    expect(state.network).to.deep.equal({ mainnet: {} });
+  });
+
+  it("Fund relayer rewards", async () => {
+    await program.methods
+      .updateRelayerReward(new anchor.BN(relayerReward))
+      .accounts({})
+      .rpc();
+    await program.methods
+      .fundRelayerRewards(new anchor.BN(anchor.web3.LAMPORTS_PER_SOL))
+      .accounts({})
+      .rpc();
+
+    const state = await program.account.btcLightClientState.fetch(
+      btcLightClientState
+    );
+    expect(state.relayerReward.toNumber()).to.equal(relayerReward);
+    expect(await provider.connection.getBalance(rewardVault)).to.equal(
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+  });
+
+  // Submits block #717695 with its timestamp replaced by `time`
+  async function submitBlock717695WithTime(time: number) {
+    const blockHeight = genesisBlock.height + 1;
+    const header = Buffer.from(block717695);
+    header.writeUInt32LE(time, 68);
+    const remainingAccounts = [
+      await createBlockHashAccountIfNeeded(program, provider, blockHeight),
+    ];
+
+    await program.methods
+      .submitBlockHeaders(new anchor.BN(blockHeight), header)
+      .accountsPartial({
+        parentBlockHashEntry: getBlockHashPda(program, blockHeight - 1),
+        headerStore: null,
+        submitterAllowlist: null,
+      })
+      .remainingAccounts(remainingAccounts)
+      .rpc();
+  }
+
+  it("Reject block time not above median time past", async () => {
+    // the ten timestamps before #717694 are zero, so the median time past is zero
+    try {
+      await submitBlock717695WithTime(0);
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include(
+        "Block time not above median time past"
+      );
+    }
+  });
+
+  it("Reject block time too far in the future", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    try {
+      await submitBlock717695WithTime(now + 3 * 60 * 60);
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("Block time too far in the future");
+    }
   });
 
   it("Submit block headers", async () => {
@@ -97,11 +179,26 @@ describe("BTC Light Client Tests", () => {
     );
 
     // submit block headers
-    await program.methods
+    const signature = await program.methods
       .submitBlockHeaders(new anchor.BN(blockHeight), Buffer.concat(headers))
-      .accounts({})
+      .accountsPartial({
+        parentBlockHashEntry: getBlockHashPda(program, blockHeight - 1),
+        headerStore: null,
+        submitterAllowlist: null,
+      })
       .remainingAccounts(remainingAccounts)
-      .rpc();
+      .rpc({ commitment: "confirmed" });
+
+    // Compute units per header, to track the cost of parsing and hashing
+    const submission = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const cuPerHeader = submission.meta.computeUnitsConsumed / headers.length;
+    if (process.env.BENCH) {
+      console.log(`submit_block_headers: ${cuPerHeader} compute units/header`);
+    }
+    expect(cuPerHeader).to.be.below(SUBMIT_HEADER_CU_LIMIT);
 
     // Verify the state after submission
     const stateAfterSubmission =
@@ -110,16 +207,15 @@ describe("BTC Light Client Tests", () => {
       "717696"
     );
     expect(stateAfterSubmission.latestBlockTime).to.equal(1641627937); // From block 717696
+    expect(stateAfterSubmission.recentBlockTimes.slice(-3)).to.deep.equal([
+      genesisBlock.time,
+      1641627659,
+      1641627937,
+    ]);
 
     // Verify block hash entries
 
-    const [block717695HashPda] = PublicKey.findProgramAddressSync(
-      [
-        Buffer.from("block_hash_entry"),
-        new anchor.BN(717695).toArrayLike(Buffer, "le", 8),
-      ],
-      program.programId
-    );
+    const block717695HashPda = getBlockHashPda(program, 717695);
 
     const block717695Hash = await program.account.blockHashEntry.fetch(
       block717695HashPda
@@ -130,6 +226,42 @@ describe("BTC Light Client Tests", () => {
     expect(Buffer.from(block717695Hash.hash).toString("hex")).to.equal(
       expectedHash
     );
+    expect(Buffer.from(stateAfterSubmission.latestChainwork)).to.not.deep.equal(
+      Buffer.from(genesisBlock.chainwork)
+    );
+
+    // the submitter is paid for both accepted headers
+    expect(await provider.connection.getBalance(rewardVault)).to.equal(
+      anchor.web3.LAMPORTS_PER_SOL - headers.length * relayerReward
+    );
+  });
+
+  // Test branch forking before the current difficulty period
+  it("Weigh a branch from the previous period by its work", async () => {
+    // #717696 opened a new period, a branch building on #717694 is still checked against
+    // the period it starts in and only loses because it carries less work
+    const blockHeight = genesisBlock.height + 1;
+    const headers = [block717695];
+    let remainingAccounts = await Promise.all(
+      headers.map((_, i) =>
+        createBlockHashAccountIfNeeded(program, provider, blockHeight + i)
+      )
+    );
+
+    try {
+      await program.methods
+        .submitBlockHeaders(new anchor.BN(blockHeight), Buffer.concat(headers))
+        .accountsPartial({
+          parentBlockHashEntry: getBlockHashPda(program, blockHeight - 1),
+          headerStore: null,
+          submitterAllowlist: null,
+        })
+        .remainingAccounts(remainingAccounts)
+        .rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("Insufficient chain work");
+    }
   });
 
   // Test submit block headers with empty headers
@@ -139,7 +271,11 @@ describe("BTC Light Client Tests", () => {
     try {
       await program.methods
         .submitBlockHeaders(new anchor.BN(blockHeight), Buffer.concat(headers))
-        .accounts({})
+        .accountsPartial({
+          parentBlockHashEntry: getBlockHashPda(program, blockHeight - 1),
+          headerStore: null,
+          submitterAllowlist: null,
+        })
         .rpc();
       expect.fail("should have thrown error");
     } catch (err) {
@@ -154,7 +290,11 @@ describe("BTC Light Client Tests", () => {
     try {
       await program.methods
         .submitBlockHeaders(new anchor.BN(blockHeight), Buffer.concat(headers))
-        .accounts({})
+        .accountsPartial({
+          parentBlockHashEntry: getBlockHashPda(program, blockHeight - 1),
+          headerStore: null,
+          submitterAllowlist: null,
+        })
         .rpc();
       expect.fail("should have thrown error");
     } catch (err) {
@@ -184,7 +324,11 @@ describe("BTC Light Client Tests", () => {
     try {
       await program.methods
         .submitBlockHeaders(new anchor.BN(blockHeight), Buffer.concat(headers))
-        .accounts({})
+        .accountsPartial({
+          parentBlockHashEntry: getBlockHashPda(program, blockHeight - 1),
+          headerStore: null,
+          submitterAllowlist: null,
+        })
         .remainingAccounts(remainingAccounts)
         .rpc();
       expect.fail("should have thrown error");
@@ -216,7 +360,11 @@ describe("BTC Light Client Tests", () => {
     try {
       await program.methods
         .submitBlockHeaders(new anchor.BN(blockHeight), Buffer.concat(headers))
-        .accounts({})
+        .accountsPartial({
+          parentBlockHashEntry: getBlockHashPda(program, blockHeight - 1),
+          headerStore: null,
+          submitterAllowlist: null,
+        })
         .remainingAccounts(remainingAccounts)
         .rpc();
       expect.fail("should have thrown error");
@@ -224,30 +372,341 @@ describe("BTC Light Client Tests", () => {
       expect(err.toString()).to.include("Invalid proof of work");
     }
   });
+
+  it("Add and remove submitters", async () => {
+    const relayer = anchor.web3.Keypair.generate();
+    const [submitterAllowlist] = PublicKey.findProgramAddressSync(
+      [Buffer.from("submitter_allowlist")],
+      program.programId
+    );
+
+    // Relaying is permissioned, so an unknown key can't submit
+    try {
+      await program.methods
+        .submitBlockHeaders(new anchor.BN(717697), Buffer.alloc(80))
+        .accountsPartial({
+          submitter: relayer.publicKey,
+          parentBlockHashEntry: getBlockHashPda(program, 717696),
+          headerStore: null,
+          submitterAllowlist: null,
+        })
+        .signers([relayer])
+        .rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("Unauthorized signer");
+    }
+
+    await program.methods.addSubmitter(relayer.publicKey).accounts({}).rpc();
+    let allowlist = await program.account.submitterAllowlist.fetch(
+      submitterAllowlist
+    );
+    expect(allowlist.submitters.map((key) => key.toBase58())).to.deep.equal([
+      relayer.publicKey.toBase58(),
+    ]);
+
+    try {
+      await program.methods.addSubmitter(relayer.publicKey).accounts({}).rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("Submitter already allowed");
+    }
+
+    await program.methods
+      .removeSubmitter(relayer.publicKey)
+      .accounts({})
+      .rpc();
+    allowlist = await program.account.submitterAllowlist.fetch(
+      submitterAllowlist
+    );
+    expect(allowlist.submitters).to.be.empty;
+
+    try {
+      await program.methods
+        .removeSubmitter(relayer.publicKey)
+        .accounts({})
+        .rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("Submitter not allowed");
+    }
+  });
+
+  it("Transfer ownership", async () => {
+    const newOwner = anchor.web3.Keypair.generate();
+    await program.methods
+      .proposeOwnership(newOwner.publicKey)
+      .accounts({})
+      .rpc();
+
+    // Only the proposed account can accept
+    const stranger = anchor.web3.Keypair.generate();
+    try {
+      await program.methods
+        .acceptOwnership()
+        .accounts({ newOwner: stranger.publicKey })
+        .signers([stranger])
+        .rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("Signer is not the pending owner");
+    }
+
+    await program.methods
+      .acceptOwnership()
+      .accounts({ newOwner: newOwner.publicKey })
+      .signers([newOwner])
+      .rpc();
+    let state = await program.account.btcLightClientState.fetch(
+      btcLightClientState
+    );
+    expect(state.owner.toBase58()).to.equal(newOwner.publicKey.toBase58());
+    expect(state.pendingOwner.toBase58()).to.equal(
+      PublicKey.default.toBase58()
+    );
+
+    // Hand ownership back for the remaining tests
+    await program.methods
+      .proposeOwnership(provider.wallet.publicKey)
+      .accounts({ authority: newOwner.publicKey })
+      .signers([newOwner])
+      .rpc();
+    await program.methods.acceptOwnership().accounts({}).rpc();
+    state = await program.account.btcLightClientState.fetch(
+      btcLightClientState
+    );
+    expect(state.owner.toBase58()).to.equal(
+      provider.wallet.publicKey.toBase58()
+    );
+  });
+
+  it("Set max reorg depth and checkpoint", async () => {
+    await program.methods
+      .updateMaxReorgDepth(new anchor.BN(6))
+      .accounts({})
+      .rpc();
+
+    const state = await program.account.btcLightClientState.fetch(
+      btcLightClientState
+    );
+    expect(state.maxReorgDepth.toNumber()).to.equal(6);
+    // The starting block is the first checkpoint
+    expect(state.checkpointHeight.toNumber()).to.equal(genesisBlock.height);
+
+    // Only a block of the current chain can be pinned
+    try {
+      await program.methods
+        .setCheckpoint(new anchor.BN(717696), Array.from(genesisBlock.hash))
+        .accountsPartial({
+          blockHashEntry: getBlockHashPda(program, 717696),
+          headerStore: null,
+        })
+        .rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("Invalid checkpoint");
+    }
+
+    await program.methods
+      .setCheckpoint(new anchor.BN(717696), state.latestBlockHash)
+      .accountsPartial({
+        blockHashEntry: getBlockHashPda(program, 717696),
+        headerStore: null,
+      })
+      .rpc();
+    const stateAfterCheckpoint =
+      await program.account.btcLightClientState.fetch(btcLightClientState);
+    expect(stateAfterCheckpoint.checkpointHeight.toNumber()).to.equal(717696);
+    expect(stateAfterCheckpoint.checkpointHash).to.deep.equal(
+      state.latestBlockHash
+    );
+  });
+
+  it("Pause and unpause header submission and verification", async () => {
+    await program.methods.pauseSubmission().accounts({}).rpc();
+    await program.methods.pauseVerification().accounts({}).rpc();
+
+    const state = await program.account.btcLightClientState.fetch(
+      btcLightClientState
+    );
+    expect(state.submissionPaused).to.be.true;
+    expect(state.verificationPaused).to.be.true;
+
+    // Pausing twice is rejected
+    try {
+      await program.methods.pauseSubmission().accounts({}).rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("Header submission already paused");
+    }
+
+    const blockHeight = genesisBlock.height + 1;
+    try {
+      await program.methods
+        .submitBlockHeaders(new anchor.BN(blockHeight), Buffer.alloc(80))
+        .accountsPartial({
+          parentBlockHashEntry: getBlockHashPda(program, blockHeight - 1),
+          headerStore: null,
+          submitterAllowlist: null,
+        })
+        .rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("Header submission paused");
+    }
+
+    await program.methods.unpauseSubmission().accounts({}).rpc();
+    await program.methods.unpauseVerification().accounts({}).rpc();
+
+    const stateAfter = await program.account.btcLightClientState.fetch(
+      btcLightClientState
+    );
+    expect(stateAfter.submissionPaused).to.be.false;
+    expect(stateAfter.verificationPaused).to.be.false;
+  });
+
+  it("Migrate state", async () => {
+    const state = await program.account.btcLightClientState.fetch(
+      btcLightClientState
+    );
+    expect(state.version).to.equal(1);
+
+    // State created by this program version is already current
+    try {
+      await program.methods
+        .migrateState(
+          {
+            blockTime: state.latestBlockTime,
+            prevBlockTimes: state.recentBlockTimes.slice(0, 10),
+            chainwork: state.latestChainwork,
+            periodStartTime: state.periodStartTime,
+          },
+          { mainnet: {} }
+        )
+        .accounts({})
+        .rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("State already at the current version");
+    }
+  });
+
+  it("Initialize header store", async () => {
+    const headerStore = anchor.web3.Keypair.generate();
+    // 8 discriminator + 8 base height + 1024 slots of 160 bytes
+    const space = 8 + 8 + 1024 * 160;
+
+    await program.methods
+      .initializeHeaderStore()
+      .accountsPartial({
+        headerStore: headerStore.publicKey,
+        tipBlockHashEntry: getBlockHashPda(program, 717696),
+      })
+      .preInstructions([
+        anchor.web3.SystemProgram.createAccount({
+          fromPubkey: provider.wallet.publicKey,
+          newAccountPubkey: headerStore.publicKey,
+          space,
+          lamports:
+            await provider.connection.getMinimumBalanceForRentExemption(space),
+          programId: program.programId,
+        }),
+      ])
+      .signers([headerStore])
+      .rpc();
+
+    const state = await program.account.btcLightClientState.fetch(
+      btcLightClientState
+    );
+    expect(state.headerStore.toBase58()).to.equal(
+      headerStore.publicKey.toBase58()
+    );
+
+    const store = await program.account.headerStore.fetch(
+      headerStore.publicKey
+    );
+    expect(store.baseHeight.toNumber()).to.equal(717696);
+    const tipSlot = store.headers[717696 % 1024];
+    expect(tipSlot.height.toNumber()).to.equal(717696);
+    expect(Buffer.from(tipSlot.hash)).to.deep.equal(
+      Buffer.from(state.latestBlockHash)
+    );
+  });
+
+  it("Submit block headers to header store without parent", async () => {
+    const state = await program.account.btcLightClientState.fetch(
+      btcLightClientState
+    );
+    // Block #717695 is only kept in its entry account, not in the store
+    try {
+      await program.methods
+        .submitBlockHeaders(new anchor.BN(717696), block717696)
+        .accountsPartial({
+          parentBlockHashEntry: null,
+          headerStore: state.headerStore,
+          submitterAllowlist: null,
+        })
+        .rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("Parent block not yet submitted");
+    }
+  });
+
+  it("Close block hash entries behind the retention depth", async () => {
+    await program.methods
+      .updateBlockEntryRetention(new anchor.BN(1))
+      .accounts({})
+      .rpc();
+
+    // Block #717694 is two blocks behind the tip, the rent goes back to its payer
+    await program.methods
+      .closeBlockHashEntry(new anchor.BN(717694))
+      .accounts({ payer: provider.wallet.publicKey })
+      .rpc();
+    expect(
+      await provider.connection.getAccountInfo(getBlockHashPda(program, 717694))
+    ).to.be.null;
+
+    try {
+      await program.methods
+        .closeBlockHashEntry(new anchor.BN(717695))
+        .accounts({ payer: provider.wallet.publicKey })
+        .rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("Block entry within retention depth");
+    }
+  });
 });
 
-async function createBlockHashAccountIfNeeded(
+function getBlockHashPda(
   program: Program<BtcLightClient>,
-  provider: anchor.AnchorProvider,
-  currentHeight: number
-): Promise<anchor.web3.AccountMeta> {
+  height: number
+): PublicKey {
   const [blockHashPda] = PublicKey.findProgramAddressSync(
     [
       Buffer.from("block_hash_entry"),
-      new anchor.BN(currentHeight).toArrayLike(Buffer, "le", 8),
+      new anchor.BN(height).toArrayLike(Buffer, "le", 8),
     ],
     program.programId
   );
+  return blockHashPda;
+}
+
+async function createBlockHashAccountIfNeeded(
+  program: Program<BtcLightClient>,
+  provider: anchor.AnchorProvider,
+  currentHeight: number
+): Promise<anchor.web3.AccountMeta> {
+  const blockHashPda = getBlockHashPda(program, currentHeight);
 
   // Check if account exists
   const accountInfo = await provider.connection.getAccountInfo(blockHashPda);
   if (!accountInfo) {
     // Create block_hash account
     const tx = await program.methods
-      .createBlockHashAccount(
-        new anchor.BN(currentHeight),
-        Array.from(new Uint8Array(32)) // Initialize with empty hash
-      )
+      .createBlockHashAccount(new anchor.BN(currentHeight))
       .accounts({})
       .rpc();
   }
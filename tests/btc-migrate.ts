@@ -0,0 +1,336 @@
+import * as anchor from "@coral-xyz/anchor";
+import { BtcLightClient } from "../target/types/btc_light_client";
+import { expect } from "chai";
+import { describe, it, before } from "node:test";
+import { PublicKey } from "@solana/web3.js";
+import { BankrunProvider } from "anchor-bankrun";
+import { Clock, startAnchor } from "solana-bankrun";
+import { createHash } from "crypto";
+
+const IDL = require("../target/idl/btc_light_client.json");
+const PROGRAM_ID = new PublicKey(IDL.address);
+
+// Migrates accounts written by the light client before its state was versioned and keeps
+// relaying on top of them
+describe("BTC Light Client Migration Tests", async () => {
+  const context = await startAnchor(
+    "",
+    [{ name: "btc_light_client", programId: PROGRAM_ID }],
+    []
+  );
+  const provider = new BankrunProvider(context);
+
+  const program = new anchor.Program<BtcLightClient>(IDL, provider);
+
+  // Block #717694, the latest block of the legacy state, two blocks before retarget
+  const legacyTip = {
+    height: 717694,
+    hash: Buffer.from(
+      "edae5e1bd8a0e007e529fe33d099ebb7a82a06d6d63d0b000000000000000000",
+      "hex"
+    ),
+    time: 1641627092,
+    target: Buffer.from(
+      "0000000000000000000B98AB0000000000000000000000000000000000000000",
+      "hex"
+    ),
+    bits: 0x170b98ab,
+  };
+  // Chain context the owner supplies for #717694
+  const chainContext = {
+    blockTime: legacyTip.time,
+    // timestamps of the ten preceding blocks, unknown here
+    prevBlockTimes: Array(10).fill(0),
+    chainwork: Array(32).fill(0),
+    // time of block #715680, the first block of the difficulty period
+    periodStartTime: 1640422999,
+  };
+
+  const block717695 = Buffer.from(
+    "04002020" + // version
+      "edae5e1bd8a0e007e529fe33d099ebb7a82a06d6d63d0b000000000000000000" +
+      "f8aec519bcd878c9713dc8153a72fd62e3667c5ade70d8d0415584b8528d79ca" +
+      "0b40d961" + // time
+      "ab980b17" + // bits
+      "3dcc4d5a", // nonce
+    "hex"
+  );
+  const block717696 = Buffer.from(
+    "00004020" + // version
+      "9acaa5d26d392ace656c2428c991b0a3d3d773845a1300000000000000000000" +
+      "aa8e225b1f3ea6c4b7afd5aa1cecf691a8beaa7fa1e579ce240e4a62b5ac8ecc" +
+      "2141d961" + // time
+      "8b8c0b17" + // bits
+      "0d5c05bb", // nonce
+    "hex"
+  );
+
+  const [statePda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("btc_light_client")],
+    program.programId
+  );
+
+  // A transaction verified before versioning
+  const legacyTxId = Buffer.alloc(32, 0x5a);
+  const [legacyTxVerifiedPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("tx_verified_state"), legacyTxId],
+    program.programId
+  );
+
+  before(async () => {
+    // Header timestamps are checked against the cluster clock
+    const clock = await context.banksClient.getClock();
+    context.setClock(
+      new Clock(
+        clock.slot,
+        clock.epochStartTimestamp,
+        clock.epoch,
+        clock.leaderScheduleEpoch,
+        BigInt(Math.floor(Date.now() / 1000))
+      )
+    );
+
+    // The legacy state: latest block, period target, is_testnet, min_confirmations, owner
+    const height = Buffer.alloc(8);
+    height.writeBigUInt64LE(BigInt(legacyTip.height));
+    const time = Buffer.alloc(4);
+    time.writeUInt32LE(legacyTip.time);
+    await setLegacyAccount(
+      statePda,
+      Buffer.concat([
+        discriminator("BtcLightClientState"),
+        legacyTip.hash,
+        height,
+        time,
+        legacyTip.target,
+        Buffer.from([0]),
+        Buffer.alloc(8),
+        provider.wallet.publicKey.toBuffer(),
+      ])
+    );
+    // The legacy entry of the latest block: height and hash
+    await setLegacyAccount(
+      getBlockHashPda(legacyTip.height),
+      Buffer.concat([discriminator("BlockHashEntry"), height, legacyTip.hash])
+    );
+    // The legacy verification record: is_verified
+    await setLegacyAccount(
+      legacyTxVerifiedPda,
+      Buffer.concat([discriminator("TxVerifiedState"), Buffer.from([1])])
+    );
+  });
+
+  it("Migrate the legacy state", async () => {
+    // the legacy state only tells mainnet from the test networks
+    try {
+      await program.methods
+        .migrateState(chainContext, { testnet3: {} })
+        .accounts({})
+        .rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include(
+        "Migrated data does not match the light client state"
+      );
+    }
+
+    await program.methods
+      .migrateState(chainContext, { mainnet: {} })
+      .accounts({})
+      .rpc();
+
+    const state = await program.account.btcLightClientState.fetch(statePda);
+    expect(state.version).to.equal(1);
+    // the version follows the discriminator in every versioned layout
+    const account = await context.banksClient.getAccount(statePda);
+    expect(account.data[8]).to.equal(1);
+    expect(state.network).to.deep.equal({ mainnet: {} });
+    expect(state.owner).to.deep.equal(provider.wallet.publicKey);
+    expect(state.latestBlockHeight.toNumber()).to.equal(legacyTip.height);
+    expect(Buffer.from(state.latestBlockHash)).to.deep.equal(legacyTip.hash);
+    expect(state.periodStartTime).to.equal(chainContext.periodStartTime);
+    expect(state.recentBlockTimes).to.deep.equal([
+      ...chainContext.prevBlockTimes,
+      legacyTip.time,
+    ]);
+    expect(state.requireCoinbaseProof).to.be.true;
+    // the latest block becomes the checkpoint, as the starting block on initialize
+    expect(state.checkpointHeight.toNumber()).to.equal(legacyTip.height);
+    expect(Buffer.from(state.checkpointHash)).to.deep.equal(legacyTip.hash);
+
+    try {
+      await program.methods
+        .migrateState(chainContext, { mainnet: {} })
+        .accounts({})
+        .rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("State already at the current version");
+    }
+  });
+
+  it("Migrate the entry of the latest block", async () => {
+    // the latest block's context must match the migrated state
+    try {
+      await program.methods
+        .migrateBlockHashEntry(
+          new anchor.BN(legacyTip.height),
+          {
+            ...chainContext,
+            periodStartTime: chainContext.periodStartTime + 1,
+          },
+          Array.from(legacyTip.target),
+          legacyTip.bits
+        )
+        .accounts({})
+        .rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include(
+        "Migrated data does not match the light client state"
+      );
+    }
+
+    await program.methods
+      .migrateBlockHashEntry(
+        new anchor.BN(legacyTip.height),
+        chainContext,
+        Array.from(legacyTip.target),
+        legacyTip.bits
+      )
+      .accounts({})
+      .rpc();
+
+    const entry = await program.account.blockHashEntry.fetch(
+      getBlockHashPda(legacyTip.height)
+    );
+    expect(entry.height.toNumber()).to.equal(legacyTip.height);
+    expect(Buffer.from(entry.hash)).to.deep.equal(legacyTip.hash);
+    expect(entry.recentBlockTimes).to.deep.equal([
+      ...chainContext.prevBlockTimes,
+      legacyTip.time,
+    ]);
+    expect(entry.bits).to.equal(legacyTip.bits);
+    expect(Buffer.from(entry.periodTarget)).to.deep.equal(legacyTip.target);
+    expect(entry.periodStartTime).to.equal(chainContext.periodStartTime);
+    expect(entry.payer).to.deep.equal(provider.wallet.publicKey);
+  });
+
+  it("Migrate a legacy verification record", async () => {
+    // the legacy record is unreadable until migrated
+    try {
+      await program.account.txVerifiedState.fetch(legacyTxVerifiedPda);
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).not.to.include("should have thrown error");
+    }
+
+    await program.methods
+      .migrateTxVerifiedState(Array.from(legacyTxId))
+      .accounts({})
+      .rpc();
+
+    // the record awaits a new proof, whose rent refund goes to the migrating payer
+    const record = await program.account.txVerifiedState.fetch(
+      legacyTxVerifiedPda
+    );
+    expect(record.isVerified).to.be.false;
+    expect(record.payer).to.deep.equal(provider.wallet.publicKey);
+    expect(record.outputs).to.be.empty;
+
+    const account = await context.banksClient.getAccount(legacyTxVerifiedPda);
+    const rent = await context.banksClient.getRent();
+    expect(BigInt(account.lamports)).to.equal(
+      rent.minimumBalance(BigInt(account.data.length))
+    );
+
+    try {
+      await program.methods
+        .migrateTxVerifiedState(Array.from(legacyTxId))
+        .accounts({})
+        .rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("State already at the current version");
+    }
+  });
+
+  it("Submit headers on top of the migrated chain", async () => {
+    // #717696 starts a new difficulty period, retargeted from the migrated context
+    const headers = [block717695, block717696];
+    const remainingAccounts = [];
+    for (let i = 0; i < headers.length; i++) {
+      remainingAccounts.push(
+        await createBlockHashAccount(legacyTip.height + 1 + i)
+      );
+    }
+    await program.methods
+      .submitBlockHeaders(
+        new anchor.BN(legacyTip.height + 1),
+        Buffer.concat(headers)
+      )
+      .accountsPartial({
+        parentBlockHashEntry: getBlockHashPda(legacyTip.height),
+        headerStore: null,
+        submitterAllowlist: null,
+      })
+      .remainingAccounts(remainingAccounts)
+      .rpc();
+
+    const state = await program.account.btcLightClientState.fetch(statePda);
+    expect(state.latestBlockHeight.toNumber()).to.equal(717696);
+    expect(Buffer.from(state.latestBlockHash)).to.deep.equal(
+      sha256d(block717696)
+    );
+    expect(state.periodStartTime).to.equal(block717696.readUInt32LE(68));
+  });
+
+  // Writes `data` to `address` as a program account holding just the rent it needs
+  async function setLegacyAccount(address: PublicKey, data: Buffer) {
+    const rent = await context.banksClient.getRent();
+    context.setAccount(address, {
+      lamports: Number(rent.minimumBalance(BigInt(data.length))),
+      data,
+      owner: program.programId,
+      executable: false,
+    });
+  }
+
+  function getBlockHashPda(height: number): PublicKey {
+    const [blockHashPda] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("block_hash_entry"),
+        new anchor.BN(height).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    return blockHashPda;
+  }
+
+  async function createBlockHashAccount(
+    height: number
+  ): Promise<anchor.web3.AccountMeta> {
+    await program.methods
+      .createBlockHashAccount(new anchor.BN(height))
+      .accounts({})
+      .rpc();
+    return {
+      pubkey: getBlockHashPda(height),
+      isWritable: true,
+      isSigner: false,
+    };
+  }
+});
+
+// Anchor account discriminator of the account type `name`
+function discriminator(name: string): Buffer {
+  return createHash("sha256")
+    .update(`account:${name}`)
+    .digest()
+    .subarray(0, 8);
+}
+
+function sha256d(data: Buffer): Buffer {
+  const sha256 = (data: Buffer) => createHash("sha256").update(data).digest();
+  return sha256(sha256(data));
+}
@@ -0,0 +1,749 @@
+import * as anchor from "@coral-xyz/anchor";
+import { BtcLightClient } from "../target/types/btc_light_client";
+import { expect } from "chai";
+import { describe, it, before } from "node:test";
+import { PublicKey } from "@solana/web3.js";
+import { BankrunProvider } from "anchor-bankrun";
+import { Clock, startAnchor } from "solana-bankrun";
+import { createHash } from "crypto";
+
+const IDL = require("../target/idl/btc_light_client.json");
+const PROGRAM_ID = new PublicKey(IDL.address);
+
+// Ceiling on the compute units spent per submitted header, so a change bringing back header
+// copies or software hashing fails the suite. `yarn bench` prints the measured costs.
+const SUBMIT_HEADER_CU_LIMIT = 40_000;
+
+// Regtest headers carry next to no work, so these tests mine their own branches on top of a
+// real regtest block
+describe("BTC Light Client Regtest Tests", async () => {
+  const context = await startAnchor(
+    "",
+    [{ name: "btc_light_client", programId: PROGRAM_ID }],
+    []
+  );
+  const provider = new BankrunProvider(context);
+
+  const program = new anchor.Program<BtcLightClient>(IDL, provider);
+
+  // Regtest block #107, the compact block test vector of rust-bitcoin: a coinbase with a
+  // witness commitment and one P2SH-P2WPKH transaction
+  const block107 = Buffer.from(
+    "00000020" + // version
+      "6c750a364035aefd5f81508a08769975116d9195312ee4520dceac39e1fdc62c" + // prev hash
+      "4dc67473b8e354358c1e610afeaff7410858bd45df43e2940f8a62bd3d5e3ac9" + // merkle root
+      "43c2975c" + // time
+      "ffff7f20" + // bits
+      "00000000", // nonce
+    "hex"
+  );
+
+  const coinbase107 = Buffer.from(
+    "020000000001010000000000000000000000000000000000000000000000000000000000" +
+      "000000ffffffff04016b0101ffffffff020006062a0100000001510000000000000000" +
+      "266a24aa21a9ed4a3d9f3343dafcc0d6f6d4310f2ee5ce273ed34edca6c75db3a73e7f" +
+      "368734200120000000000000000000000000000000000000000000000000000000000000" +
+      "000000000000",
+    "hex"
+  );
+  const tx107 = Buffer.from(
+    "020000000001021fc20ba2bd745507b8e00679e3b362558f9457db374ca28ffa5243f4c2" +
+      "3a4d5f00000000171600147c9dea14ffbcaec4b575e03f05ceb7a81cd3fcbffdffffff91" +
+      "5d689be87b43337f42e26033df59807b768223368f189a023d0242d83776890000000017" +
+      "1600147c9dea14ffbcaec4b575e03f05ceb7a81cd3fcbffdffffff0200cdf50500000000" +
+      "17a9146803c72d9154a6a20f404bed6d3dcee07986235a8700e1f5050000000017a9144e" +
+      "6a4c7cb5b5562904843bdf816342f4db9f5797870247304402205e9bf6e70eb0e4b495bf" +
+      "483fd8e6e02da64900f290ef8aaa64bb32600d973c450220670896f5d0e5f33473e5f399" +
+      "ab680cc1d25c2d2afd15abd722f04978f28be887012103e4e4d9312b2261af508b367d8b" +
+      "a9be4f01b61d6d6e78bec499845b4f410bcf2702473044022045ac80596a6ac9c8c572f9" +
+      "4708709adaf106677221122e08daf8b9741a04f66a022003ccd52a3b78f8fd08058fc04f" +
+      "c0cffa5f4c196c84eae9e37e2a85babe731b57012103e4e4d9312b2261af508b367d8ba9" +
+      "be4f01b61d6d6e78bec499845b4f410bcf276a000000",
+    "hex"
+  );
+  // txid in internal byte order
+  const tx107Id = Buffer.from(
+    "d71f558878242d46a97ca057ef93b100d374c6671dc9eb87bc845f16c7a35a7f",
+    "hex"
+  );
+
+  const genesisBlock = {
+    height: 107,
+    hash: sha256d(block107),
+    time: block107.readUInt32LE(68),
+    // timestamps of the ten preceding blocks, unknown here
+    prevBlockTimes: Array(10).fill(0),
+    // regtest target, 0x207fffff
+    target: Buffer.concat([Buffer.from("7fffff", "hex"), Buffer.alloc(29)]),
+    chainwork: Buffer.alloc(32),
+    periodStartTime: block107.readUInt32LE(68),
+  };
+
+  const [statePda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("btc_light_client")],
+    program.programId
+  );
+
+  // Latest block on the canonical chain, which the next mined block extends
+  let tip = { header: block107, height: genesisBlock.height };
+
+  before(async () => {
+    // Header timestamps are checked against the cluster clock
+    const clock = await context.banksClient.getClock();
+    context.setClock(
+      new Clock(
+        clock.slot,
+        clock.epochStartTimestamp,
+        clock.epoch,
+        clock.leaderScheduleEpoch,
+        BigInt(Math.floor(Date.now() / 1000))
+      )
+    );
+
+    await program.methods
+      .initialize(
+        new anchor.BN(genesisBlock.height),
+        Array.from(genesisBlock.hash),
+        Array.from(genesisBlock.target),
+        {
+          blockTime: genesisBlock.time,
+          prevBlockTimes: genesisBlock.prevBlockTimes,
+          chainwork: Array.from(genesisBlock.chainwork),
+          periodStartTime: genesisBlock.periodStartTime,
+        },
+        { regtest: {} },
+        new anchor.BN(0)
+      )
+      .accounts({})
+      .rpc();
+  });
+
+  // Witness proof of the P2SH-P2WPKH transaction of block #107, checking its 1 BTC output
+  function witnessProof(witnessMerkleProof: Buffer[], txIndex = 1) {
+    return {
+      blockHeader: block107,
+      txId: Array.from(tx107Id),
+      txIndex: txIndex,
+      rawTx: tx107,
+      witnessMerkleProof: witnessMerkleProof.map((node) => Array.from(node)),
+      coinbaseProof: {
+        coinbaseTx: coinbase107,
+        merkleProof: [Array.from(tx107Id)],
+      },
+      expectedOutputs: [
+        {
+          index: 1,
+          minAmount: new anchor.BN(100000000),
+          script: {
+            p2sh: {
+              scriptHash: Array.from(
+                Buffer.from("4e6a4c7cb5b5562904843bdf816342f4db9f5797", "hex")
+              ),
+            },
+          },
+        },
+      ],
+      recipientOutput: null,
+    };
+  }
+
+  it("Verify tx through the witness commitment", async () => {
+    const [txVerifiedStatePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("tx_verified_state"), tx107Id],
+      program.programId
+    );
+
+    // the coinbase's own wtxid is no sibling, its leaf in the witness tree is zeros
+    try {
+      await program.methods
+        .verifyTransactionWitness(
+          new anchor.BN(genesisBlock.height),
+          witnessProof([sha256d(coinbase107)])
+        )
+        .accountsPartial({
+          blockHashEntry: getBlockHashPda(genesisBlock.height),
+          headerStore: null,
+        })
+        .rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("Invalid witness commitment");
+    }
+
+    // an index bit beyond the proof length would be ignored by the fold
+    try {
+      await program.methods
+        .verifyTransactionWitness(
+          new anchor.BN(genesisBlock.height),
+          witnessProof([Buffer.alloc(32)], 3)
+        )
+        .accountsPartial({
+          blockHashEntry: getBlockHashPda(genesisBlock.height),
+          headerStore: null,
+        })
+        .rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("Invalid merkle proof");
+    }
+
+    await program.methods
+      .verifyTransactionWitness(
+        new anchor.BN(genesisBlock.height),
+        witnessProof([Buffer.alloc(32)])
+      )
+      .accountsPartial({
+        blockHashEntry: getBlockHashPda(genesisBlock.height),
+        headerStore: null,
+      })
+      .rpc();
+
+    const txState = await program.account.txVerifiedState.fetch(
+      txVerifiedStatePda
+    );
+    expect(txState.isVerified).to.be.true;
+    expect(Buffer.from(txState.wtxid)).to.deep.equal(sha256d(tx107));
+    expect(txState.outputs.length).to.equal(1);
+    expect(txState.outputs[0].index).to.equal(1);
+    expect(txState.outputs[0].value.toNumber()).to.equal(100000000);
+  });
+
+  it("Finalize header buffer", async () => {
+    const startHeight = genesisBlock.height + 1;
+    const headers = mineBranch(block107, 4);
+    const [headerBuffer] = PublicKey.findProgramAddressSync(
+      [Buffer.from("header_buffer"), provider.wallet.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .initHeaderBuffer(new anchor.BN(startHeight))
+      .accountsPartial({
+        parentBlockHashEntry: getBlockHashPda(genesisBlock.height),
+        headerStore: null,
+      })
+      .rpc();
+
+    // every chunk is validated as it is appended
+    for (let i = 0; i < headers.length; i += 2) {
+      await program.methods
+        .appendHeaderBuffer(Buffer.concat(headers.slice(i, i + 2)))
+        .accounts({})
+        .rpc();
+    }
+    try {
+      await program.methods
+        .appendHeaderBuffer(mineHeader(headers[1], 0))
+        .accounts({})
+        .rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("Invalid previous block hash");
+    }
+
+    const buffer = await program.account.headerBuffer.fetch(headerBuffer);
+    expect(buffer.startHeight.toNumber()).to.equal(startHeight);
+    expect(buffer.count.toNumber()).to.equal(headers.length);
+    expect(Buffer.from(buffer.parentHash)).to.deep.equal(genesisBlock.hash);
+    expect(Buffer.from(buffer.tip.hash)).to.deep.equal(
+      sha256d(headers[headers.length - 1])
+    );
+
+    // the tip only moves once the buffer is finalized
+    let state = await program.account.btcLightClientState.fetch(statePda);
+    expect(state.latestBlockHeight.toNumber()).to.equal(genesisBlock.height);
+
+    // without a header store, every buffered header needs its entry account
+    const remainingAccounts = [];
+    for (let i = 0; i < headers.length; i++) {
+      remainingAccounts.push(await createBlockHashAccount(startHeight + i));
+    }
+    try {
+      await program.methods
+        .finalizeHeaderBuffer()
+        .accountsPartial({
+          parentBlockHashEntry: getBlockHashPda(genesisBlock.height),
+          headerStore: null,
+          submitterAllowlist: null,
+        })
+        .remainingAccounts(remainingAccounts.slice(1))
+        .rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("Invalid account number");
+    }
+
+    await program.methods
+      .finalizeHeaderBuffer()
+      .accountsPartial({
+        parentBlockHashEntry: getBlockHashPda(genesisBlock.height),
+        headerStore: null,
+        submitterAllowlist: null,
+      })
+      .remainingAccounts(remainingAccounts)
+      .rpc();
+
+    state = await program.account.btcLightClientState.fetch(statePda);
+    expect(state.latestBlockHeight.toNumber()).to.equal(
+      genesisBlock.height + headers.length
+    );
+    expect(Buffer.from(state.latestBlockHash)).to.deep.equal(
+      sha256d(headers[headers.length - 1])
+    );
+    const entry = await program.account.blockHashEntry.fetch(
+      getBlockHashPda(startHeight)
+    );
+    expect(Buffer.from(entry.hash)).to.deep.equal(sha256d(headers[0]));
+    expect(await context.banksClient.getAccount(headerBuffer)).to.be.null;
+    tip = {
+      header: headers[headers.length - 1],
+      height: genesisBlock.height + headers.length,
+    };
+  });
+
+  it("Measure header submission compute units", async () => {
+    const headers = mineBranch(tip.header, 6, 1);
+    const submission = await (await submitHeaders(tip.height, headers))
+      .preInstructions([
+        anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({
+          units: 1_400_000,
+        }),
+      ])
+      .transaction();
+    submission.recentBlockhash = context.lastBlockhash;
+    submission.feePayer = context.payer.publicKey;
+    submission.sign(context.payer);
+    const result = await context.banksClient.tryProcessTransaction(submission);
+    expect(result.result).to.be.null;
+
+    const cuPerHeader =
+      Number(result.meta.computeUnitsConsumed) / headers.length;
+    if (process.env.BENCH) {
+      console.log(`submit_block_headers: ${cuPerHeader} compute units/header`);
+    }
+    expect(cuPerHeader).to.be.below(SUBMIT_HEADER_CU_LIMIT);
+    tip = {
+      header: headers[headers.length - 1],
+      height: tip.height + headers.length,
+    };
+  });
+
+  it("Prove every output paying a proven script", async () => {
+    const depositScript = p2wsh(Buffer.from("deposit"));
+    const tx = legacyTx([
+      { value: 50000, script: depositScript },
+      { value: 30000, script: depositScript },
+      { value: 10000, script: p2wsh(Buffer.from("change")) },
+      { value: 0, script: recipientScript(provider.wallet.publicKey) },
+      { value: 0, script: recipientScript(PublicKey.unique()) },
+    ]);
+    const block = await submitBlock(tx);
+    const depositOutput = (index: number, amount: number) => ({
+      index,
+      minAmount: new anchor.BN(amount),
+      script: {
+        p2wsh: { scriptHash: Array.from(sha256(Buffer.from("deposit"))) },
+      },
+    });
+
+    // the output at index 1 pays the same script, so leaving it out hides value
+    try {
+      await verifyInBlock(block, tx, [depositOutput(0, 50000)]).rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include(
+        "Expected outputs must include every output paying the same script"
+      );
+    }
+
+    // with two OP_RETURN outputs the prover could pick either recipient
+    try {
+      await verifyInBlock(
+        block,
+        tx,
+        [depositOutput(0, 50000), depositOutput(1, 30000)],
+        3
+      ).rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("Invalid recipient commitment");
+    }
+
+    await verifyInBlock(block, tx, [
+      depositOutput(0, 50000),
+      depositOutput(1, 30000),
+    ]).rpc();
+    const txState = await program.account.txVerifiedState.fetch(
+      getTxVerifiedStatePda(txid(tx))
+    );
+    expect(txState.outputs.map((output) => output.index)).to.deep.equal([0, 1]);
+  });
+
+  it("Verify a transaction larger than 32KB from a buffer", async () => {
+    // a 40KB witness item pushes the serialization past the transaction and heap limits,
+    // while the txid only covers the stripped bytes
+    const outputs = [
+      { value: 70000, script: p2wsh(Buffer.from("large deposit")) },
+    ];
+    const stripped = legacyTx(outputs);
+    const rawTx = witnessTx(outputs, Buffer.alloc(40000, 0x51));
+    expect(rawTx.length).to.be.above(32 * 1024);
+    const block = await submitBlock(stripped);
+    const txId = txid(stripped);
+
+    const [txBufferPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("tx_buffer"), provider.wallet.publicKey.toBuffer(), txId],
+      program.programId
+    );
+    await program.methods.initTxBuffer(Array.from(txId)).accounts({}).rpc();
+    const chunkSize = 900;
+    for (let offset = 0; offset < rawTx.length; offset += chunkSize) {
+      await program.methods
+        .appendTxBuffer(
+          Array.from(txId),
+          offset,
+          rawTx.subarray(offset, offset + chunkSize)
+        )
+        .accounts({})
+        .rpc();
+    }
+
+    await program.methods
+      .verifyTransactionFromBuffer(new anchor.BN(block.height), {
+        blockHeader: block.header,
+        txId: Array.from(txId),
+        txIndex: 1,
+        merkleProof: [Array.from(txid(block.coinbase))],
+        expectedOutputs: [
+          {
+            index: 0,
+            minAmount: new anchor.BN(70000),
+            script: {
+              p2wsh: {
+                scriptHash: Array.from(sha256(Buffer.from("large deposit"))),
+              },
+            },
+          },
+        ],
+        recipientOutput: null,
+        coinbaseProof: {
+          coinbaseTx: block.coinbase,
+          merkleProof: [Array.from(txId)],
+        },
+      })
+      .accountsPartial({
+        blockHashEntry: getBlockHashPda(block.height),
+        headerStore: null,
+      })
+      .preInstructions([
+        anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({
+          units: 1_400_000,
+        }),
+      ])
+      .rpc();
+
+    const txState = await program.account.txVerifiedState.fetch(
+      getTxVerifiedStatePda(txId)
+    );
+    expect(txState.outputs.map((output) => output.index)).to.deep.equal([0]);
+    // the buffer is closed once verified
+    expect(await context.banksClient.getAccount(txBufferPda)).to.be.null;
+  });
+
+  it("Reject reorgs past the max depth or the checkpoint", async () => {
+    const base = tip;
+    const chain = mineBranch(base.header, 3, 2);
+    await (await submitHeaders(base.height, chain)).rpc();
+    tip = { header: chain[2], height: base.height + 3 };
+
+    // a heavier branch replacing all three blocks is one block too deep
+    await program.methods
+      .updateMaxReorgDepth(new anchor.BN(2))
+      .accounts({})
+      .rpc();
+    const deepBranch = mineBranch(base.header, 4, 3);
+    try {
+      await (await submitHeaders(base.height, deepBranch)).rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("Reorg exceeds the maximum depth");
+    }
+
+    // a pinned block cannot be replaced, even within the max depth
+    await program.methods
+      .setCheckpoint(
+        new anchor.BN(base.height + 2),
+        Array.from(sha256d(chain[1]))
+      )
+      .accountsPartial({
+        blockHashEntry: getBlockHashPda(base.height + 2),
+        headerStore: null,
+      })
+      .rpc();
+    const pinnedBranch = mineBranch(chain[0], 3, 4);
+    try {
+      await (await submitHeaders(base.height + 1, pinnedBranch)).rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("Headers conflict with a checkpoint");
+    }
+
+    // above the checkpoint and within the max depth, the heavier branch wins
+    const branch = mineBranch(chain[1], 2, 5);
+    await (await submitHeaders(base.height + 2, branch)).rpc();
+    const state = await program.account.btcLightClientState.fetch(statePda);
+    expect(Buffer.from(state.latestBlockHash)).to.deep.equal(
+      sha256d(branch[1])
+    );
+    tip = { header: branch[1], height: base.height + 4 };
+  });
+
+  it("Let a heavier branch across a period boundary win", async () => {
+    // extend the chain to the last block of the first difficulty period
+    const boundary = 2016;
+    while (tip.height < boundary - 1) {
+      const count = Math.min(6, boundary - 1 - tip.height);
+      const headers = mineBranch(tip.header, count, 6);
+      await (await submitHeaders(tip.height, headers)).rpc();
+      tip = { header: headers[count - 1], height: tip.height + count };
+    }
+    const lastOfPeriod = tip;
+    const [firstOfPeriod] = mineBranch(lastOfPeriod.header, 1, 7);
+    await (await submitHeaders(lastOfPeriod.height, [firstOfPeriod])).rpc();
+
+    // the rival branch starts in the previous period of the tip and carries more work
+    const rival = mineBranch(lastOfPeriod.header, 2, 8);
+    await (await submitHeaders(lastOfPeriod.height, rival)).rpc();
+
+    const state = await program.account.btcLightClientState.fetch(statePda);
+    expect(state.latestBlockHeight.toNumber()).to.equal(boundary + 1);
+    expect(Buffer.from(state.latestBlockHash)).to.deep.equal(
+      sha256d(rival[1])
+    );
+    const entry = await program.account.blockHashEntry.fetch(
+      getBlockHashPda(boundary)
+    );
+    expect(Buffer.from(entry.hash)).to.deep.equal(sha256d(rival[0]));
+    expect(entry.periodStartTime).to.equal(rival[0].readUInt32LE(68));
+    tip = { header: rival[1], height: boundary + 1 };
+  });
+
+  // Mines a block holding a coinbase and `tx` on top of the tip and submits its header
+  async function submitBlock(tx: Buffer) {
+    const height = tip.height + 1;
+    const coinbase = coinbaseTx(height);
+    const header = mineHeader(
+      tip.header,
+      height,
+      sha256d(Buffer.concat([txid(coinbase), txid(tx)]))
+    );
+    await (await submitHeaders(tip.height, [header])).rpc();
+    tip = { header, height };
+    return { header, height, coinbase };
+  }
+
+  // Submission of `headers` building on the block at `parentHeight`
+  async function submitHeaders(parentHeight: number, headers: Buffer[]) {
+    const remainingAccounts = [];
+    for (let i = 0; i < headers.length; i++) {
+      const height = parentHeight + 1 + i;
+      remainingAccounts.push(await createBlockHashAccount(height));
+    }
+    return program.methods
+      .submitBlockHeaders(
+        new anchor.BN(parentHeight + 1),
+        Buffer.concat(headers)
+      )
+      .accountsPartial({
+        parentBlockHashEntry: getBlockHashPda(parentHeight),
+        headerStore: null,
+        submitterAllowlist: null,
+      })
+      .remainingAccounts(remainingAccounts);
+  }
+
+  // Proof of `tx`, the second transaction of `block`, checking `expectedOutputs`
+  function verifyInBlock(
+    block: { header: Buffer; height: number; coinbase: Buffer },
+    tx: Buffer,
+    expectedOutputs: any[],
+    recipientOutput: number | null = null
+  ) {
+    return program.methods
+      .verifyTransaction(new anchor.BN(block.height), {
+        blockHeader: block.header,
+        txId: Array.from(txid(tx)),
+        txIndex: 1,
+        merkleProof: [Array.from(txid(block.coinbase))],
+        rawTx: tx,
+        expectedOutputs,
+        recipientOutput,
+        coinbaseProof: {
+          coinbaseTx: block.coinbase,
+          merkleProof: [Array.from(txid(tx))],
+        },
+      })
+      .accountsPartial({
+        blockHashEntry: getBlockHashPda(block.height),
+        headerStore: null,
+      });
+  }
+
+  function getTxVerifiedStatePda(txId: Buffer): PublicKey {
+    const [txVerifiedStatePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("tx_verified_state"), txId],
+      program.programId
+    );
+    return txVerifiedStatePda;
+  }
+
+  function getBlockHashPda(height: number): PublicKey {
+    const [blockHashPda] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("block_hash_entry"),
+        new anchor.BN(height).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    return blockHashPda;
+  }
+
+  async function createBlockHashAccount(
+    height: number
+  ): Promise<anchor.web3.AccountMeta> {
+    const pubkey = getBlockHashPda(height);
+    if (!(await context.banksClient.getAccount(pubkey))) {
+      await program.methods
+        .createBlockHashAccount(new anchor.BN(height))
+        .accounts({})
+        .rpc();
+    }
+    return { pubkey, isWritable: true, isSigner: false };
+  }
+});
+
+function sha256(data: Buffer): Buffer {
+  return createHash("sha256").update(data).digest();
+}
+
+function sha256d(data: Buffer): Buffer {
+  return sha256(sha256(data));
+}
+
+// txid in internal byte order of a transaction serialized without witness
+function txid(tx: Buffer): Buffer {
+  return sha256d(tx);
+}
+
+function compactSize(n: number): Buffer {
+  if (n < 0xfd) {
+    return Buffer.from([n]);
+  }
+  const size = Buffer.alloc(n <= 0xffff ? 3 : 5);
+  size[0] = n <= 0xffff ? 0xfd : 0xfe;
+  if (n <= 0xffff) {
+    size.writeUInt16LE(n, 1);
+  } else {
+    size.writeUInt32LE(n, 1);
+  }
+  return size;
+}
+
+function p2wsh(witnessScript: Buffer): Buffer {
+  return Buffer.concat([Buffer.from([0x00, 0x20]), sha256(witnessScript)]);
+}
+
+// `OP_RETURN <recipient>` committing a pegin to a Solana account
+function recipientScript(recipient: PublicKey): Buffer {
+  return Buffer.concat([Buffer.from([0x6a, 0x20]), recipient.toBuffer()]);
+}
+
+// Coinbase of a block at `height`, without witness
+function coinbaseTx(height: number): Buffer {
+  const scriptSig = Buffer.alloc(5);
+  scriptSig[0] = 4;
+  scriptSig.writeUInt32LE(height, 1);
+  return Buffer.concat([
+    Buffer.from("01000000", "hex"), // version
+    compactSize(1),
+    Buffer.alloc(32), // null prevout
+    Buffer.from("ffffffff", "hex"),
+    compactSize(scriptSig.length),
+    scriptSig,
+    Buffer.from("ffffffff", "hex"), // sequence
+    compactSize(1),
+    Buffer.alloc(8), // value
+    compactSize(1),
+    Buffer.from([0x51]), // OP_TRUE
+    Buffer.alloc(4), // lock time
+  ]);
+}
+
+// Transaction without witness spending a made-up outpoint to `outputs`
+function legacyTx(outputs: { value: number; script: Buffer }[]): Buffer {
+  const parts = [
+    Buffer.from("02000000", "hex"), // version
+    compactSize(1),
+    sha256(Buffer.from(`prevout ${outputs.length}`)),
+    Buffer.alloc(4), // vout
+    compactSize(0), // empty scriptSig
+    Buffer.from("ffffffff", "hex"), // sequence
+    compactSize(outputs.length),
+  ];
+  for (const output of outputs) {
+    const value = Buffer.alloc(8);
+    value.writeBigUInt64LE(BigInt(output.value));
+    parts.push(value, compactSize(output.script.length), output.script);
+  }
+  parts.push(Buffer.alloc(4)); // lock time
+  return Buffer.concat(parts);
+}
+
+// `legacyTx(outputs)` with a segwit marker and `witnessItem` as the only witness of its input
+function witnessTx(
+  outputs: { value: number; script: Buffer }[],
+  witnessItem: Buffer
+): Buffer {
+  const stripped = legacyTx(outputs);
+  return Buffer.concat([
+    stripped.subarray(0, 4), // version
+    Buffer.from([0x00, 0x01]), // marker and flag
+    stripped.subarray(4, stripped.length - 4),
+    compactSize(1),
+    compactSize(witnessItem.length),
+    witnessItem,
+    stripped.subarray(stripped.length - 4), // lock time
+  ]);
+}
+
+// Mines a regtest header building on `parent`, ten minutes after it. `salt` goes into the
+// merkle root, so sibling blocks of different branches get different hashes.
+function mineHeader(
+  parent: Buffer,
+  salt: number,
+  merkleRoot: Buffer = sha256d(Buffer.from(`salt ${salt}`))
+): Buffer {
+  const header = Buffer.alloc(80);
+  header.writeUInt32LE(0x20000000, 0);
+  sha256d(parent).copy(header, 4);
+  merkleRoot.copy(header, 36);
+  header.writeUInt32LE(parent.readUInt32LE(68) + 600, 68);
+  header.writeUInt32LE(0x207fffff, 72);
+  // the regtest target is 0x7fffff followed by 29 zero bytes, the hash is little-endian
+  for (let nonce = 0; ; nonce++) {
+    header.writeUInt32LE(nonce, 76);
+    if (sha256d(header)[31] < 0x7f) {
+      return header;
+    }
+  }
+}
+
+// Mines `count` consecutive headers on top of `parent`
+function mineBranch(parent: Buffer, count: number, salt = 0): Buffer[] {
+  const headers: Buffer[] = [];
+  for (let i = 0; i < count; i++) {
+    parent = mineHeader(parent, salt * 1000 + i);
+    headers.push(parent);
+  }
+  return headers;
+}
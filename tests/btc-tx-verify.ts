@@ -10,6 +10,10 @@ import { startAnchor } from "solana-bankrun";
 const IDL = require("../target/idl/btc_light_client.json");
 const PROGRAM_ID = new PublicKey(IDL.address);
 
+// Ceiling on the compute units of a full `verify_transaction`, so a change bringing back
+// copies or software hashing fails the suite. `yarn bench` prints the measured costs.
+const VERIFY_TX_CU_LIMIT = 150_000;
+
 describe("BTC TX Verify Tests", async () => {
   const context = await startAnchor(
     "",
@@ -27,10 +31,14 @@ describe("BTC TX Verify Tests", async () => {
       "hex"
     ),
     time: 1736757020,
+    // timestamps of the ten preceding blocks, unknown here
+    prevBlockTimes: Array(10).fill(0),
     target: Buffer.from(
       "0003400100000000000000000000000000000000000000000000000000000000",
       "hex"
     ),
+    chainwork: Buffer.alloc(32),
+    periodStartTime: 1736757020,
   };
 
   let btcLightClientState: PublicKey;
@@ -45,9 +53,14 @@ describe("BTC TX Verify Tests", async () => {
     .initialize(
       new anchor.BN(genesisBlock.height),
       Array.from(genesisBlock.hash),
-      genesisBlock.time,
       Array.from(genesisBlock.target),
-      false,
+      {
+        blockTime: genesisBlock.time,
+        prevBlockTimes: genesisBlock.prevBlockTimes,
+        chainwork: Array.from(genesisBlock.chainwork),
+        periodStartTime: genesisBlock.periodStartTime,
+      },
+      { signet: {} },
       new anchor.BN(0)
     )
     .accounts({})
@@ -67,99 +80,339 @@ describe("BTC TX Verify Tests", async () => {
   expect(Buffer.from(state.latestPeriodTarget).toString("hex")).to.equal(
     Buffer.from(genesisBlock.target).toString("hex")
   );
-  expect(state.isTestnet).to.be.false;
+  expect(state.network).to.deep.equal({ signet: {} });
+  expect(state.requireCoinbaseProof).to.be.true;
+
+  // signet block 230627 holds a deposit to a P2WSH script
+  const blockHeight = 230627;
+  const blockHeader = Buffer.from(
+    "0000002005cd6ba37338a8c37e431180aa2d2175c39d9a6bdf3954653cb0630420000000a553f5c27318e0d7afbcf242942a0fd099683a6e6670f4a68dc8ab9ddfd7761a1ccf8467ad46011edb6c4401",
+    "hex"
+  );
+  const txId = Buffer.from(
+    "c6c911614166de26173be7c90ba37a0a26c44c3dac9bb69f84ef5b35d7525026",
+    "hex"
+  );
+  const rawTx = Buffer.from(
+    "020000000326a07fe8edcaf04e6e77508064437abf02ee0d22864849af908a3fbaaa5a992a0000000000ffffffff0ac2fb6b10869caec02d6d509fde9b2d0af3f808985ebfb623c085a0110d02840000000000ffffffffd58d5ca8fe86fd28bbb4530fa7133e2d68d263e43c453fefead6c25b42dc69ff0000000000ffffffff03a08601000000000022002085f1940c71a1e1a852db646fa0f79cf1e5defc9e4bda671ad4cf9000ada74b41881300000000000022512052d19a46c1a8cd90001a816420448b612d9c13bdb50d02d716d411deb94dc930e208000000000000225120e1382c1cb56e91bc45683199f550261b4a2da8a6db7454f3e236a4e3dfba890c00000000",
+    "hex"
+  );
+  const txIndex = 378;
+  const outputIndex = 0;
+  const expectedAmount = 100000;
+  const expectedScriptHash = Buffer.from(
+    "85f1940c71a1e1a852db646fa0f79cf1e5defc9e4bda671ad4cf9000ada74b41",
+    "hex"
+  );
+  const txMerkleProof = [
+    "d1263d3e754e1167d9a68b7c4ca98b245b696ecc18badea92e49c55c0729bd1a",
+    "20aeeae156d22ffb9a128ede8555de27c3c6bd9f47647e3de7bd4b332f8d5086",
+    "67e6ea2dd621b45e7aa1ff1d20977851b4694c25db628bc1359072e83fe0a2af",
+    "83579ca714156e1ad2832cbc4b0c1ca2d599e2cc46f6d31e967c844415c6767a",
+    "17fc9275ca3b6d678b77ab3677a7da37c65997893f7c10db67b5552c628f7d7f",
+    "50c7a085616cca1e17a766a1c581eede0139f5b0c03a8e37dfe3c2d8c2798e0a",
+    "239c7c1ea91e3868721ef686ca893a40b1d532d637a3fefd53df1de9aba847ab",
+    "0a117857964bc8182f3ddc1cdda9c71b3a081989d0aa3404be924ea3c1507671",
+    "23fae37f988d3dcc5cf4ed21139d9e9c3b35d68b2c1cbfaf21b67fb606cd0954",
+    "7a4491c685b8ae32f9ae1266a193ac335ca8f722c47af58d4e2c8283892ed091",
+  ].map((hash) => Array.from(Buffer.from(hash, "hex")));
+  const depositOutput = {
+    index: outputIndex,
+    minAmount: new anchor.BN(expectedAmount),
+    script: { p2wsh: { scriptHash: Array.from(expectedScriptHash) } },
+  };
+  const blockHashEntry = PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("block_hash_entry"),
+      new anchor.BN(blockHeight).toArrayLike(Buffer, "le", 8),
+    ],
+    program.programId
+  )[0];
+  const [btcTxStatePda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("tx_verified_state"), txId],
+    program.programId
+  );
+  const computeBudget = anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({
+    units: 1_000_000,
+  });
+
+  // Proof of the deposit output, with fields overridden per test
+  const txProof = (overrides = {}) => ({
+    blockHeader: blockHeader,
+    txId: Array.from(txId),
+    txIndex: txIndex,
+    merkleProof: txMerkleProof,
+    rawTx: rawTx,
+    expectedOutputs: [depositOutput],
+    recipientOutput: null,
+    coinbaseProof: null,
+    ...overrides,
+  });
+  const verifyTx = (overrides = {}) =>
+    program.methods
+      .verifyTransaction(new anchor.BN(blockHeight), txProof(overrides))
+      .accountsPartial({ blockHashEntry, headerStore: null })
+      .preInstructions([computeBudget]);
+
+  it("Require a coinbase proof by default", async () => {
+    try {
+      await verifyTx().rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("Coinbase proof required");
+    }
+
+    // These signet fixtures come without the block's coinbase
+    await program.methods.updateRequireCoinbaseProof(false).accounts({}).rpc();
+  });
 
   it("Verify tx", async () => {
-    // signet Block 230627 block header
-    const blockHeight = 230627;
-    const blockHeader = Buffer.from(
-      "0000002005cd6ba37338a8c37e431180aa2d2175c39d9a6bdf3954653cb0630420000000a553f5c27318e0d7afbcf242942a0fd099683a6e6670f4a68dc8ab9ddfd7761a1ccf8467ad46011edb6c4401",
-      "hex"
+    await verifyTx().rpc();
+
+    const txState = await program.account.txVerifiedState.fetch(btcTxStatePda);
+    expect(txState.isVerified).to.be.true;
+    expect(Buffer.from(txState.wtxid)).to.deep.equal(Buffer.alloc(32));
+    expect(txState.blockHeight.toNumber()).to.equal(blockHeight);
+    expect(txState.outputs.length).to.equal(1);
+    expect(txState.outputs[0].index).to.equal(outputIndex);
+    expect(txState.outputs[0].value.toNumber()).to.equal(expectedAmount);
+    expect(txState.recipient).to.be.null;
+    // proven in the checkpoint block
+    expect(txState.isFinal).to.be.true;
+  });
+
+  it("Reject an output under another script type", async () => {
+    // The same 32-byte payload as a taproot key is a different output
+    try {
+      await verifyTx({
+        expectedOutputs: [
+          {
+            ...depositOutput,
+            script: { p2tr: { outputKey: Array.from(expectedScriptHash) } },
+          },
+        ],
+      }).rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("Invalid output script");
+    }
+  });
+
+  it("Reject a recipient commitment outside OP_RETURN", async () => {
+    try {
+      await verifyTx({ recipientOutput: 1 }).rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("Invalid recipient commitment");
+    }
+  });
+
+  it("Add outputs from a later proof", async () => {
+    // One proof can cover the deposit output and the taproot output next to it
+    await verifyTx({
+      expectedOutputs: [
+        depositOutput,
+        {
+          index: 1,
+          minAmount: new anchor.BN(5000),
+          script: {
+            p2tr: {
+              outputKey: Array.from(
+                Buffer.from(
+                  "52d19a46c1a8cd90001a816420448b612d9c13bdb50d02d716d411deb94dc930",
+                  "hex"
+                )
+              ),
+            },
+          },
+        },
+      ],
+    }).rpc();
+
+    const txState = await program.account.txVerifiedState.fetch(btcTxStatePda);
+    expect(txState.outputs.map((output) => output.index)).to.deep.equal([0, 1]);
+    expect(txState.outputs[1].value.toNumber()).to.equal(5000);
+  });
+
+  it("Verify spend", async () => {
+    // The deposit spends the outpoint of its first input
+    const spentTxId = rawTx.subarray(5, 37);
+    await program.methods
+      .verifySpend(new anchor.BN(blockHeight), {
+        blockHeader: blockHeader,
+        txId: Array.from(txId),
+        txIndex: txIndex,
+        merkleProof: txMerkleProof,
+        rawTx: rawTx,
+        coinbaseProof: null,
+        inputIndex: 0,
+        spentTxId: Array.from(spentTxId),
+        spentVout: 0,
+        expectedSequence: 0xffffffff,
+        expectedLockTime: 0,
+      })
+      .accountsPartial({ blockHashEntry, headerStore: null })
+      .preInstructions([computeBudget])
+      .rpc();
+
+    const [verifiedSpendPda] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("verified_spend"),
+        spentTxId,
+        new anchor.BN(0).toArrayLike(Buffer, "le", 4),
+      ],
+      program.programId
     );
-    const txId = Buffer.from(
-      "c6c911614166de26173be7c90ba37a0a26c44c3dac9bb69f84ef5b35d7525026",
-      "hex"
+    const verifiedSpend = await program.account.verifiedSpend.fetch(
+      verifiedSpendPda
     );
+    expect(Buffer.from(verifiedSpend.spendingTxId)).to.deep.equal(txId);
+    expect(verifiedSpend.inputIndex).to.equal(0);
+    expect(verifiedSpend.blockHeight.toNumber()).to.equal(blockHeight);
+  });
 
-    const rawTx = Buffer.from(
-      "020000000326a07fe8edcaf04e6e77508064437abf02ee0d22864849af908a3fbaaa5a992a0000000000ffffffff0ac2fb6b10869caec02d6d509fde9b2d0af3f808985ebfb623c085a0110d02840000000000ffffffffd58d5ca8fe86fd28bbb4530fa7133e2d68d263e43c453fefead6c25b42dc69ff0000000000ffffffff03a08601000000000022002085f1940c71a1e1a852db646fa0f79cf1e5defc9e4bda671ad4cf9000ada74b41881300000000000022512052d19a46c1a8cd90001a816420448b612d9c13bdb50d02d716d411deb94dc930e208000000000000225120e1382c1cb56e91bc45683199f550261b4a2da8a6db7454f3e236a4e3dfba890c00000000",
-      "hex"
-    );
+  it("Reject a witness commitment outside the coinbase", async () => {
+    try {
+      await program.methods
+        .verifyTransactionWitness(new anchor.BN(blockHeight), {
+          blockHeader: blockHeader,
+          txId: Array.from(txId),
+          txIndex: txIndex,
+          rawTx: rawTx,
+          witnessMerkleProof: txMerkleProof,
+          coinbaseProof: { coinbaseTx: rawTx, merkleProof: txMerkleProof },
+          expectedOutputs: [depositOutput],
+          recipientOutput: null,
+        })
+        .accountsPartial({ blockHashEntry, headerStore: null })
+        .preInstructions([computeBudget])
+        .rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("Invalid coinbase proof");
+    }
+  });
 
-    const txIndex = 378;
-    const outputIndex = 0;
-    const expectedAmount = 100000;
-    const expectedScriptHash = Buffer.from(
-      "85f1940c71a1e1a852db646fa0f79cf1e5defc9e4bda671ad4cf9000ada74b41",
-      "hex"
+  it("Verify tx from buffer", async () => {
+    // Upload the raw transaction in two chunks, the second one first
+    const [txBufferPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("tx_buffer"), provider.wallet.publicKey.toBuffer(), txId],
+      program.programId
     );
-    const txMerkleProof = [
-      Buffer.from(
-        "d1263d3e754e1167d9a68b7c4ca98b245b696ecc18badea92e49c55c0729bd1a",
-        "hex"
-      ),
-      Buffer.from(
-        "20aeeae156d22ffb9a128ede8555de27c3c6bd9f47647e3de7bd4b332f8d5086",
-        "hex"
-      ),
-      Buffer.from(
-        "67e6ea2dd621b45e7aa1ff1d20977851b4694c25db628bc1359072e83fe0a2af",
-        "hex"
-      ),
-      Buffer.from(
-        "83579ca714156e1ad2832cbc4b0c1ca2d599e2cc46f6d31e967c844415c6767a",
-        "hex"
-      ),
-      Buffer.from(
-        "17fc9275ca3b6d678b77ab3677a7da37c65997893f7c10db67b5552c628f7d7f",
-        "hex"
-      ),
-      Buffer.from(
-        "50c7a085616cca1e17a766a1c581eede0139f5b0c03a8e37dfe3c2d8c2798e0a",
-        "hex"
-      ),
-      Buffer.from(
-        "239c7c1ea91e3868721ef686ca893a40b1d532d637a3fefd53df1de9aba847ab",
-        "hex"
-      ),
-      Buffer.from(
-        "0a117857964bc8182f3ddc1cdda9c71b3a081989d0aa3404be924ea3c1507671",
-        "hex"
-      ),
-      Buffer.from(
-        "23fae37f988d3dcc5cf4ed21139d9e9c3b35d68b2c1cbfaf21b67fb606cd0954",
-        "hex"
-      ),
-      Buffer.from(
-        "7a4491c685b8ae32f9ae1266a193ac335ca8f722c47af58d4e2c8283892ed091",
-        "hex"
-      ),
-    ];
+    await program.methods.initTxBuffer(Array.from(txId)).accounts({}).rpc();
+    for (const offset of [150, 0]) {
+      const chunk = offset ? rawTx.subarray(offset) : rawTx.subarray(0, 150);
+      await program.methods
+        .appendTxBuffer(Array.from(txId), offset, chunk)
+        .accounts({})
+        .rpc();
+    }
+    // The raw bytes follow the discriminator and the tx id
+    const txBuffer = await context.banksClient.getAccount(txBufferPda);
+    expect(Buffer.from(txBuffer.data.subarray(8 + 32))).to.deep.equal(rawTx);
+
     await program.methods
-      .verifyTransaction(new anchor.BN(blockHeight), {
+      .verifyTransactionFromBuffer(new anchor.BN(blockHeight), {
         blockHeader: blockHeader,
         txId: Array.from(txId),
         txIndex: txIndex,
-        merkleProof: txMerkleProof.map((proof) => Array.from(proof)),
-        rawTx: rawTx,
-        outputIndex: outputIndex,
-        expectedAmount: new anchor.BN(expectedAmount),
-        expectedScriptHash: Array.from(expectedScriptHash),
+        merkleProof: txMerkleProof,
+        expectedOutputs: [depositOutput],
+        recipientOutput: null,
+        coinbaseProof: null,
       })
-      .accounts({})
-      .preInstructions([
-        anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({
-          units: 1_000_000,
-        }),
-      ])
+      .accountsPartial({ blockHashEntry, headerStore: null })
+      .preInstructions([computeBudget])
       .rpc();
 
-    const [btcTxStatePda] = PublicKey.findProgramAddressSync(
-      [Buffer.from("tx_verified_state"), txId],
+    // The buffer is closed once verified
+    expect(await context.banksClient.getAccount(txBufferPda)).to.be.null;
+  });
+
+  it("Measure verification compute units", async () => {
+    // Compute units of a full verification, to track the cost of the hashing
+    const benchTx = await verifyTx().transaction();
+    benchTx.recentBlockhash = context.lastBlockhash;
+    benchTx.feePayer = context.payer.publicKey;
+    benchTx.sign(context.payer);
+    const simulation = await context.banksClient.simulateTransaction(benchTx);
+    expect(simulation.result).to.be.null;
+    const cu = Number(simulation.meta.computeUnitsConsumed);
+    if (process.env.BENCH) {
+      console.log(`verify_transaction: ${cu} compute units`);
+    }
+    expect(cu).to.be.below(VERIFY_TX_CU_LIMIT);
+  });
+
+  it("Query the light client", async () => {
+    const tip = await program.methods.getTip().accounts({}).view();
+    expect(tip.blockHeight.toNumber()).to.equal(genesisBlock.height);
+    expect(Buffer.from(tip.blockHash)).to.deep.equal(genesisBlock.hash);
+    expect(tip.blockTime).to.equal(genesisBlock.time);
+
+    const blockHash = await program.methods
+      .getBlockHash(new anchor.BN(blockHeight))
+      .accountsPartial({ blockHashEntry, headerStore: null })
+      .view();
+    expect(Buffer.from(blockHash)).to.deep.equal(genesisBlock.hash);
+
+    // The transaction was verified in the tip block
+    const confirmations = await program.methods
+      .getTxConfirmations(Array.from(txId))
+      .accountsPartial({ blockHashEntry, headerStore: null })
+      .view();
+    expect(confirmations.toNumber()).to.equal(1);
+
+    // That block is the checkpoint, so it is final and its entry is not needed
+    const finalConfirmations = await program.methods
+      .getTxConfirmations(Array.from(txId))
+      .accountsPartial({ blockHashEntry: null, headerStore: null })
+      .view();
+    expect(finalConfirmations.toNumber()).to.equal(1);
+
+    // The ten timestamps before the genesis block are unknown zeros
+    const medianTimePast = await program.methods
+      .getMedianTimePast()
+      .accounts({})
+      .view();
+    expect(medianTimePast).to.equal(0);
+  });
+
+  it("Set signet challenge", async () => {
+    // 1-of-2 bare multisig challenge of the public signet
+    const challenge = Buffer.from(
+      "512103ad5e0edad18cb1f0fc0d28a3d4f1f3e445640337489abb10404f2d1e086be430" +
+        "210359ef5021964fe22d6f8e05b2463c9540ce96883fe3b278760f048f5189f2e6c452ae",
+      "hex"
+    );
+    await program.methods.setSignetChallenge(challenge).accounts({}).rpc();
+
+    const [signetConfigPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("signet_config")],
       program.programId
     );
+    const signetConfig = await program.account.signetConfig.fetch(
+      signetConfigPda
+    );
+    expect(Buffer.from(signetConfig.challenge)).to.deep.equal(challenge);
+  });
 
-    const txState = await program.account.txVerifiedState.fetch(btcTxStatePda);
-    expect(txState.isVerified).to.be.true;
+  it("Submit signet headers without block solution should fail", async () => {
+    try {
+      await program.methods
+        .submitBlockHeaders(
+          new anchor.BN(genesisBlock.height + 1),
+          Buffer.alloc(80)
+        )
+        .accountsPartial({
+          parentBlockHashEntry: null,
+          headerStore: null,
+          submitterAllowlist: null,
+        })
+        .rpc();
+      expect.fail("should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("Signet headers need a block solution");
+    }
   });
 });